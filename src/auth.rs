@@ -1,59 +1,304 @@
 //! Contains the auth methods you can use for the client.
 
-use crate::{client::route::Route, error::Error, model::misc::Params};
+use crate::{
+    auth::{scope::Scope, store::TokenStore},
+    client::{
+        endpoints::Endpoints,
+        transport::{HttpRequest, HttpTransport},
+    },
+    error::Error,
+};
 use async_trait::async_trait;
-use reqwest::Response;
+use futures::lock::Mutex;
+use http::Method;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
 
+pub mod application;
 pub mod basic;
+pub mod scope;
+pub mod store;
+pub mod totp;
+pub mod web;
 
-///The trait handling authentication and GET requests for different authentication methods.
+/// How long before the expiration the access token gets refreshed.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+/// The trait handling authentication for different authentication methods.
+/// The auth method owns the current token, the client asks it for a valid one before every request.
 #[async_trait]
 pub trait Auth {
-    /// Perfors the authentication via the reddit API.
-    async fn login(&self) -> Result<String, Error> {
-        unimplemented!()
-    }
+    /// Performs the authentication via the reddit API, returning a new token.
+    /// `current` is the token being replaced, which may hold a refresh token.
+    async fn login(
+        &self,
+        transport: &dyn HttpTransport,
+        endpoints: &Endpoints,
+        current: Option<&Token>,
+    ) -> Result<Token, Error>;
 
-    /// Performs a GET request using the auth method.
-    async fn get(
+    /// Revokes the tokens and clears the token store, any further request fails with `Error::LoggedOut`.
+    async fn logout(
         &self,
-        _route: Route,
-        _key: &str,
-        _user_agent: &str,
-        _params: &Params,
-    ) -> Result<Response, Error> {
-        unimplemented!()
+        transport: &dyn HttpTransport,
+        endpoints: &Endpoints,
+    ) -> Result<(), Error>;
+
+    /// The cache holding the current token of this auth method.
+    fn token_cache(&self) -> &TokenCache;
+
+    /// Whether this auth method is limited to reading, refusing any action.
+    fn read_only(&self) -> bool {
+        false
     }
+}
 
-    /// Performs a POST request using the auth method.
-    async fn post(
-        &self,
-        _route: Route,
-        _key: &str,
-        _user_agent: &str,
-        _params: &Params,
-    ) -> Result<Response, Error> {
-        unimplemented!()
+/// An access token returned by reddit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Token {
+    /// The token sent along with every request.
+    pub access_token: String,
+    /// The token used to get a new access token, only given for permanent authorizations.
+    pub refresh_token: Option<String>,
+    /// When the access token expires.
+    pub expires_at: SystemTime,
+    /// The scopes granted to the token, empty if reddit did not report them.
+    pub scopes: Vec<Scope>,
+}
+
+impl Token {
+    /// Whether the access token expired or is about to.
+    pub fn is_expired(&self) -> bool {
+        SystemTime::now() + EXPIRY_MARGIN >= self.expires_at
+    }
+
+    /// Whether the token can be used for the given scope.
+    /// Tokens reddit reported no scopes for are assumed to have all of them, leaving the check to reddit.
+    pub fn has_scope(&self, scope: &Scope) -> bool {
+        self.scopes.is_empty() || self.scopes.contains(&Scope::All) || self.scopes.contains(scope)
+    }
+}
+
+/// Holds the current token of an auth method.
+/// Concurrent requests wait for a single refresh instead of each logging in again.
+/// With a `TokenStore`, the token is saved after every login and shared with other workers using the same store.
+#[derive(Debug, Default)]
+pub struct TokenCache {
+    token: Mutex<Option<Token>>,
+    store: Option<Box<dyn TokenStore>>,
+    logged_out: AtomicBool,
+}
+
+impl TokenCache {
+    /// Creates an empty cache, the first request will log in.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a cache holding an expired token, so the refresh token is used on the first request.
+    pub fn with_refresh_token(refresh_token: &str) -> Self {
+        TokenCache {
+            token: Mutex::new(Some(Token {
+                access_token: String::new(),
+                refresh_token: Some(refresh_token.to_string()),
+                expires_at: SystemTime::UNIX_EPOCH,
+                scopes: Vec::new(),
+            })),
+            store: None,
+            logged_out: AtomicBool::new(false),
+        }
+    }
+
+    /// Sets the store the token is loaded from and saved to.
+    pub fn set_store<S: TokenStore + 'static>(&mut self, store: S) {
+        self.store = Some(Box::new(store));
+    }
+
+    /// Returns a copy of the current token, if there is one.
+    pub async fn get(&self) -> Option<Token> {
+        self.token.lock().await.clone()
     }
 
-    /// Performs a DELETE request using the auth method.
-    async fn delete(
+    /// Returns a valid token, logging in first if the current one expired.
+    pub(crate) async fn token<A: Auth + Sync + ?Sized>(
         &self,
-        _route: Route,
-        _key: &str,
-        _user_agent: &str,
-    ) -> Result<Response, Error> {
-        unimplemented!()
+        auth: &A,
+        transport: &dyn HttpTransport,
+        endpoints: &Endpoints,
+    ) -> Result<Token, Error> {
+        // The lock is held while logging in, so other requests wait for the new token.
+        let mut token = self.token.lock().await;
+        if self.logged_out.load(Ordering::SeqCst) {
+            return Err(Error::LoggedOut);
+        }
+        if let Some(current) = &*token {
+            if !current.is_expired() {
+                return Ok(current.clone());
+            }
+        }
+
+        if let Some(store) = &self.store {
+            // Another worker may have refreshed the token already, or a previous run left one behind.
+            if let Some(stored) = store.load().await? {
+                let rejected = token
+                    .as_ref()
+                    .map_or(false, |t| t.access_token == stored.access_token);
+                if !stored.is_expired() && !rejected {
+                    *token = Some(stored.clone());
+                    return Ok(stored);
+                }
+                if token.is_none() || stored.refresh_token.is_some() {
+                    *token = Some(stored);
+                }
+            }
+        }
+
+        let new = auth.login(transport, endpoints, token.as_ref()).await?;
+        if let Some(store) = &self.store {
+            store.save(&new).await?;
+        }
+        *token = Some(new.clone());
+        Ok(new)
     }
 
-    /// Performs a POST request using the auth method.
-    async fn put(
+    /// Forgets the token and revokes it, starting with the refresh token since that also invalidates its access tokens.
+    /// The cache stays logged out and both tokens are revoked even if clearing the store or one of the revocations fails,
+    /// in which case the first error is returned.
+    pub async fn logout(
         &self,
-        _route: Route,
-        _key: &str,
-        _user_agent: &str,
-        _params: &Params,
-    ) -> Result<Response, Error> {
-        unimplemented!()
+        transport: &dyn HttpTransport,
+        endpoints: &Endpoints,
+        client_id: &str,
+        secret_key: &str,
+    ) -> Result<(), Error> {
+        let mut token = self.token.lock().await;
+        self.logged_out.store(true, Ordering::SeqCst);
+        let current = token.take();
+        drop(token);
+
+        let cleared = match &self.store {
+            Some(store) => store.clear().await,
+            None => Ok(()),
+        };
+
+        // Both tokens are revoked even if one fails, the first error is returned afterwards.
+        let mut revoked = Vec::new();
+        if let Some(current) = current {
+            if let Some(refresh_token) = &current.refresh_token {
+                revoked.push(
+                    revoke_token(
+                        transport,
+                        endpoints,
+                        client_id,
+                        secret_key,
+                        refresh_token,
+                        "refresh_token",
+                    )
+                    .await,
+                );
+            }
+            if !current.access_token.is_empty() {
+                revoked.push(
+                    revoke_token(
+                        transport,
+                        endpoints,
+                        client_id,
+                        secret_key,
+                        &current.access_token,
+                        "access_token",
+                    )
+                    .await,
+                );
+            }
+        }
+        revoked.into_iter().fold(cleared, Result::and)
+    }
+
+    /// Marks the access token as expired if it is still the current one, after reddit rejected it.
+    pub(crate) async fn invalidate(&self, access_token: &str) {
+        let mut token = self.token.lock().await;
+        if let Some(current) = token.as_mut() {
+            if current.access_token == access_token {
+                current.expires_at = SystemTime::UNIX_EPOCH;
+            }
+        }
+    }
+}
+
+/// Requests a new token using the given grant.
+/// The refresh token and scopes of `current` are kept if reddit does not return new ones.
+pub(crate) async fn request_token(
+    transport: &dyn HttpTransport,
+    endpoints: &Endpoints,
+    client_id: &str,
+    secret_key: &str,
+    form: &[(&str, &str)],
+    current: Option<&Token>,
+) -> Result<Token, Error> {
+    let request = HttpRequest::new(Method::POST, &endpoints.access_token_url())
+        .basic_auth(client_id, secret_key)?
+        .form(form)?;
+    let response = transport.send(request).await?;
+
+    let json: serde_json::Value = serde_json::from_str(&response.body)
+        .map_err(|_| Error::Status(response.status.as_u16()))?;
+    let map = json.as_object().ok_or("Bad response")?;
+
+    if let Some(error) = map.get("error") {
+        return Err(Error::Custom(format!("Authorization failed: {}", error)));
+    }
+    if !response.status.is_success() {
+        return Err(Error::Status(response.status.as_u16()));
+    }
+
+    let expiration: u64 = map
+        .get("expires_in")
+        .ok_or("No `expires_in` field in response")?
+        .as_u64()
+        .ok_or("`expires_in` field is an invalid type")?;
+
+    let token: String = map
+        .get("access_token")
+        .ok_or("No `access_token` field in response")?
+        .as_str()
+        .ok_or("`access_token` field is an invalid type")?
+        .to_string();
+
+    let refresh_token = map
+        .get("refresh_token")
+        .and_then(|r| r.as_str())
+        .map(|r| r.to_string())
+        .or_else(|| current.and_then(|c| c.refresh_token.clone()));
+
+    let scopes = match map.get("scope").and_then(|s| s.as_str()) {
+        Some(scope) => Scope::parse_list(scope),
+        None => current.map(|c| c.scopes.clone()).unwrap_or_default(),
+    };
+
+    Ok(Token {
+        access_token: token,
+        refresh_token,
+        expires_at: SystemTime::now() + Duration::from_secs(expiration),
+        scopes,
+    })
+}
+
+/// Revokes an access or refresh token, `hint` naming which of the two it is.
+pub(crate) async fn revoke_token(
+    transport: &dyn HttpTransport,
+    endpoints: &Endpoints,
+    client_id: &str,
+    secret_key: &str,
+    token: &str,
+    hint: &str,
+) -> Result<(), Error> {
+    let request = HttpRequest::new(Method::POST, &endpoints.revoke_token_url())
+        .basic_auth(client_id, secret_key)?
+        .form(&[("token", token), ("token_type_hint", hint)])?;
+    let response = transport.send(request).await?;
+    if !response.status.is_success() {
+        return Err(Error::Status(response.status.as_u16()));
     }
+    Ok(())
 }
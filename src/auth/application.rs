@@ -0,0 +1,104 @@
+//! The application-only authentication method, for read-only access without a reddit account.
+
+use crate::{
+    auth::{request_token, store::TokenStore, Auth, Token, TokenCache},
+    client::{endpoints::Endpoints, transport::HttpTransport},
+    error::Error,
+};
+use async_trait::async_trait;
+
+/// The device ID reddit asks for when the application does not track devices.
+const UNTRACKED_DEVICE: &str = "DO_NOT_TRACK_THIS_DEVICE";
+
+/// The authentication method for applications without a reddit account.
+/// It only allows reading, any action that would change something fails with `Error::ReadOnly`.
+#[derive(Debug)]
+pub struct ApplicationAuth {
+    /// The application ID.
+    pub client_id: String,
+    /// The secret OAuth application key, empty for installed applications.
+    pub secret_key: String,
+    /// The unique ID of the device, only used by installed applications.
+    pub device_id: Option<String>,
+    token_cache: TokenCache,
+}
+
+impl ApplicationAuth {
+    /// Builder for the ApplicationAuth method, using the `client_credentials` grant.
+    /// It requires the use of the "script" or "web app" account type.
+    pub async fn new(client_id: &str, secret_key: &str) -> Self {
+        ApplicationAuth {
+            client_id: client_id.to_string(),
+            secret_key: secret_key.to_string(),
+            device_id: None,
+            token_cache: TokenCache::new(),
+        }
+    }
+
+    /// Builder for the ApplicationAuth method, using the `installed_client` grant.
+    /// It requires the use of the "installed app" account type, which has no secret key.
+    /// Without a device ID, reddit is told not to track the device.
+    pub async fn installed(client_id: &str, device_id: Option<&str>) -> Self {
+        ApplicationAuth {
+            client_id: client_id.to_string(),
+            secret_key: String::new(),
+            device_id: Some(device_id.unwrap_or(UNTRACKED_DEVICE).to_string()),
+            token_cache: TokenCache::new(),
+        }
+    }
+
+    /// Loads and saves the token using the given store, resuming the session of a previous run if possible.
+    pub fn with_store<S: TokenStore + 'static>(mut self, store: S) -> Self {
+        self.token_cache.set_store(store);
+        self
+    }
+}
+
+#[async_trait]
+impl Auth for ApplicationAuth {
+    async fn login(
+        &self,
+        transport: &dyn HttpTransport,
+        endpoints: &Endpoints,
+        _current: Option<&Token>,
+    ) -> Result<Token, Error> {
+        let form: Vec<(&str, &str)> = match &self.device_id {
+            Some(device_id) => vec![
+                (
+                    "grant_type",
+                    "https://oauth.reddit.com/grants/installed_client",
+                ),
+                ("device_id", device_id),
+            ],
+            None => vec![("grant_type", "client_credentials")],
+        };
+
+        request_token(
+            transport,
+            endpoints,
+            &self.client_id,
+            &self.secret_key,
+            &form,
+            None,
+        )
+        .await
+    }
+
+    async fn logout(
+        &self,
+        transport: &dyn HttpTransport,
+        endpoints: &Endpoints,
+    ) -> Result<(), Error> {
+        self.token_cache
+            .logout(transport, endpoints, &self.client_id, &self.secret_key)
+            .await
+    }
+
+    fn token_cache(&self) -> &TokenCache {
+        &self.token_cache
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+}
@@ -1,10 +1,17 @@
 //! The basic authentication method, using a reddit username and password.
 
-use crate::{auth::Auth, client::route::Route, error::Error, model::misc::Params};
+use crate::{
+    auth::{
+        request_token,
+        store::TokenStore,
+        totp::{Totp, TwoFactor},
+        Auth, Token, TokenCache,
+    },
+    client::{endpoints::Endpoints, transport::HttpTransport},
+    error::Error,
+};
 use async_trait::async_trait;
-use reqwest::{Client as HttpClient, Response};
-use std::sync::{Arc, RwLock};
-use std::time::{Duration, SystemTime};
+use std::sync::Arc;
 
 /// The basic authentication method for Reddit bots.
 /// It requires the use of the "script" account type.
@@ -18,8 +25,8 @@ pub struct BasicAuth {
     pub username: String,
     /// The password of the bot account.
     pub password: String,
-    pub(crate) expiration: Arc<RwLock<Duration>>,
-    http_client: HttpClient,
+    two_factor: Option<TwoFactor>,
+    token_cache: TokenCache,
 }
 
 impl BasicAuth {
@@ -30,197 +37,73 @@ impl BasicAuth {
             secret_key: secret_key.to_string(),
             username: username.to_string(),
             password: password.to_string(),
-            http_client: HttpClient::new(),
-            expiration: Arc::new(RwLock::new(Duration::from_secs(3600))),
+            two_factor: None,
+            token_cache: TokenCache::new(),
         }
     }
-}
-
-#[async_trait]
-impl Auth for BasicAuth {
-    async fn login(&self) -> Result<String, Error> {
-        let response = self
-            .http_client
-            .post("https://www.reddit.com/api/v1/access_token")
-            .basic_auth(&self.client_id, Some(&self.secret_key))
-            .body(format!(
-                "grant_type=password&username={}&password={}",
-                self.username, self.password
-            ))
-            .send()
-            .await?
-            .text()
-            .await?;
-
-        let json: serde_json::Value = serde_json::from_str(&response)?;
-        let map = json.as_object().ok_or_else(|| "Bad response")?;
 
-        let expiration: u64 = map
-            .get("expires_in")
-            .ok_or_else(|| "No `expires_in` field in response")?
-            .as_u64()
-            .ok_or_else(|| "`expires_in` field is an invalid type")?;
-
-        {
-            let mut exp = self
-                .expiration
-                .write()
-                .map_err(|_| "Expiration lock is poisoned")?;
-            *exp = Duration::from_secs(expiration);
-        }
-
-        let token: String = map
-            .get("access_token")
-            .ok_or_else(|| "No `access_token` field in response")?
-            .as_str()
-            .ok_or_else(|| "`access_token` field is an invalid type")?
-            .to_string();
-
-        Ok(token)
+    /// Enables two-factor authentication using the base32 TOTP secret of the account.
+    /// A fresh code is generated for every login, so token refreshes keep working.
+    pub fn with_totp(mut self, secret: &str) -> Result<Self, Error> {
+        self.two_factor = Some(TwoFactor::Totp(Totp::new(secret)?));
+        Ok(self)
     }
 
-    async fn get(
-        &self,
-        route: Route,
-        key: &str,
-        user_agent: &str,
-        params: &Params,
-    ) -> Result<Response, Error> {
-        let mut login = false;
-        {
-            let exp = self
-                .expiration
-                .read()
-                .map_err(|_| "Expiration lock is poisoned")?;
-            if SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .expect("Your system time is before Linux Epoch")
-                > *exp
-            {
-                login = true;
-            };
-        }
-
-        if login {
-            self.login().await?;
-        };
-
-        let request = self
-            .http_client
-            .get(&format!("{}{}", route.to_string(), "?raw_json=1"))
-            .header("User-Agent", user_agent)
-            .query(&params)
-            .bearer_auth(key);
-
-        let response = request.send().await?;
-
-        Ok(response)
+    /// Enables two-factor authentication, asking the callback for the current code on every login.
+    pub fn with_code_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        self.two_factor = Some(TwoFactor::Callback(Arc::new(callback)));
+        self
     }
 
-    async fn post(
-        &self,
-        route: Route,
-        key: &str,
-        user_agent: &str,
-        params: &Params,
-    ) -> Result<Response, Error> {
-        let mut login = false;
-        {
-            let exp = self
-                .expiration
-                .read()
-                .map_err(|_| "Expiration lock is poisoned")?;
-            if SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .expect("Your system time is before Linux Epoch")
-                > *exp
-            {
-                login = true;
-            };
-        }
-
-        if login {
-            self.login().await?;
-        };
-
-        let request = self
-            .http_client
-            .post(&format!("{}{}", route.to_string(), "?raw_json=1"))
-            .header("User-Agent", user_agent)
-            .query(&params)
-            .bearer_auth(key);
-
-        let response = request.send().await?;
-
-        Ok(response)
+    /// Loads and saves the token using the given store, resuming the session of a previous run if possible.
+    pub fn with_store<S: TokenStore + 'static>(mut self, store: S) -> Self {
+        self.token_cache.set_store(store);
+        self
     }
+}
 
-    async fn delete(&self, route: Route, key: &str, user_agent: &str) -> Result<Response, Error> {
-        let mut login = false;
-        {
-            let exp = self
-                .expiration
-                .read()
-                .map_err(|_| "Expiration lock is poisoned")?;
-            if SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .expect("Your system time is before Linux Epoch")
-                > *exp
-            {
-                login = true;
-            };
-        }
-
-        if login {
-            self.login().await?;
+#[async_trait]
+impl Auth for BasicAuth {
+    async fn login(
+        &self,
+        transport: &dyn HttpTransport,
+        endpoints: &Endpoints,
+        _current: Option<&Token>,
+    ) -> Result<Token, Error> {
+        // Reddit expects the two-factor code appended to the password.
+        let password = match &self.two_factor {
+            Some(two_factor) => format!("{}:{}", self.password, two_factor.code()),
+            None => self.password.clone(),
         };
-
-        let request = self
-            .http_client
-            .delete(&format!("{}{}", route.to_string(), "?raw_json=1"))
-            .header("User-Agent", user_agent)
-            .bearer_auth(key);
-
-        let response = request.send().await?;
-
-        Ok(response)
+        request_token(
+            transport,
+            endpoints,
+            &self.client_id,
+            &self.secret_key,
+            &[
+                ("grant_type", "password"),
+                ("username", &self.username),
+                ("password", &password),
+            ],
+            None,
+        )
+        .await
     }
 
-    async fn put(
+    async fn logout(
         &self,
-        route: Route,
-        key: &str,
-        user_agent: &str,
-        params: &Params,
-    ) -> Result<Response, Error> {
-        let mut login = false;
-        {
-            let exp = self
-                .expiration
-                .read()
-                .map_err(|_| "Expiration lock is poisoned")?;
-            if SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .expect("Your system time is before Linux Epoch")
-                > *exp
-            {
-                login = true;
-            };
-        }
-
-        if login {
-            self.login().await?;
-        };
-
-        let request = self
-            .http_client
-            .put(&format!("{}{}", route.to_string(), "?raw_json=1"))
-            .header("User-Agent", user_agent)
-            .query(&params)
-            .bearer_auth(key);
-
-        let response = request.send().await?;
+        transport: &dyn HttpTransport,
+        endpoints: &Endpoints,
+    ) -> Result<(), Error> {
+        self.token_cache
+            .logout(transport, endpoints, &self.client_id, &self.secret_key)
+            .await
+    }
 
-        Ok(response)
+    fn token_cache(&self) -> &TokenCache {
+        &self.token_cache
     }
 }
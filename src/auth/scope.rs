@@ -0,0 +1,183 @@
+//! The OAuth scopes limiting what an access token can be used for.
+
+use crate::error::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+/// An OAuth scope, granting access to a part of the reddit API.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Scope {
+    /// Every scope, granted to tokens of "script" applications.
+    All,
+    /// Update preferences and related account information.
+    Account,
+    /// Spend reddit gold creddits.
+    Creddits,
+    /// Edit and delete comments and submissions.
+    Edit,
+    /// Select flair in subreddits.
+    Flair,
+    /// Access the voting history and saved or hidden items.
+    History,
+    /// Access the username and signup date.
+    Identity,
+    /// Manage live threads.
+    LiveManage,
+    /// Manage the configuration, sidebar and CSS of subreddits.
+    ModConfig,
+    /// Add and remove approved submitters, and ban or mute users.
+    ModContributors,
+    /// Manage and assign flair in subreddits.
+    ModFlair,
+    /// Access the moderation log of subreddits.
+    ModLog,
+    /// Access and manage modmail.
+    ModMail,
+    /// Invite or remove other moderators.
+    ModOthers,
+    /// Approve, remove, lock and mark submissions and comments.
+    ModPosts,
+    /// Accept invitations to moderate and abdicate moderator status.
+    ModSelf,
+    /// Change editors and visibility of wiki pages.
+    ModWiki,
+    /// Access the list of subscribed and moderated subreddits.
+    MySubreddits,
+    /// Access the inbox and send private messages.
+    PrivateMessages,
+    /// Access posts and comments.
+    Read,
+    /// Report content, and hide or show submissions.
+    Report,
+    /// Save and unsave comments and submissions.
+    Save,
+    /// Edit the structured styles of subreddits.
+    StructuredStyles,
+    /// Submit links and comments.
+    Submit,
+    /// Manage subscriptions and friends.
+    Subscribe,
+    /// Vote on comments and submissions.
+    Vote,
+    /// Edit wiki pages.
+    WikiEdit,
+    /// Read wiki pages.
+    WikiRead,
+    /// A scope this library does not know, kept by the name reddit used for it.
+    Other(String),
+}
+
+impl Scope {
+    /// The name reddit uses for this scope.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Scope::All => "*",
+            Scope::Account => "account",
+            Scope::Creddits => "creddits",
+            Scope::Edit => "edit",
+            Scope::Flair => "flair",
+            Scope::History => "history",
+            Scope::Identity => "identity",
+            Scope::LiveManage => "livemanage",
+            Scope::ModConfig => "modconfig",
+            Scope::ModContributors => "modcontributors",
+            Scope::ModFlair => "modflair",
+            Scope::ModLog => "modlog",
+            Scope::ModMail => "modmail",
+            Scope::ModOthers => "modothers",
+            Scope::ModPosts => "modposts",
+            Scope::ModSelf => "modself",
+            Scope::ModWiki => "modwiki",
+            Scope::MySubreddits => "mysubreddits",
+            Scope::PrivateMessages => "privatemessages",
+            Scope::Read => "read",
+            Scope::Report => "report",
+            Scope::Save => "save",
+            Scope::StructuredStyles => "structuredstyles",
+            Scope::Submit => "submit",
+            Scope::Subscribe => "subscribe",
+            Scope::Vote => "vote",
+            Scope::WikiEdit => "wikiedit",
+            Scope::WikiRead => "wikiread",
+            Scope::Other(other) => other,
+        }
+    }
+
+    /// Parses the space separated scopes of a token response.
+    /// Scopes this library does not know are kept as [`Scope::Other`].
+    pub fn parse_list(scopes: &str) -> Vec<Scope> {
+        scopes
+            .split(&[' ', ','][..])
+            .filter(|s| !s.is_empty())
+            .filter_map(|s| s.parse().ok())
+            .collect()
+    }
+
+    /// Joins the scopes the way reddit expects them when authorizing.
+    pub fn join(scopes: &[Scope]) -> String {
+        scopes
+            .iter()
+            .map(Scope::as_str)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let scope = match s {
+            "*" => Scope::All,
+            "account" => Scope::Account,
+            "creddits" => Scope::Creddits,
+            "edit" => Scope::Edit,
+            "flair" => Scope::Flair,
+            "history" => Scope::History,
+            "identity" => Scope::Identity,
+            "livemanage" => Scope::LiveManage,
+            "modconfig" => Scope::ModConfig,
+            "modcontributors" => Scope::ModContributors,
+            "modflair" => Scope::ModFlair,
+            "modlog" => Scope::ModLog,
+            "modmail" => Scope::ModMail,
+            "modothers" => Scope::ModOthers,
+            "modposts" => Scope::ModPosts,
+            "modself" => Scope::ModSelf,
+            "modwiki" => Scope::ModWiki,
+            "mysubreddits" => Scope::MySubreddits,
+            "privatemessages" => Scope::PrivateMessages,
+            "read" => Scope::Read,
+            "report" => Scope::Report,
+            "save" => Scope::Save,
+            "structuredstyles" => Scope::StructuredStyles,
+            "submit" => Scope::Submit,
+            "subscribe" => Scope::Subscribe,
+            "vote" => Scope::Vote,
+            "wikiedit" => Scope::WikiEdit,
+            "wikiread" => Scope::WikiRead,
+            "" => return Err(Error::Custom("Empty scope".into())),
+            other => Scope::Other(other.into()),
+        };
+        Ok(scope)
+    }
+}
+
+impl Serialize for Scope {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Scope {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let scope = String::deserialize(deserializer)?;
+        scope.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,132 @@
+//! Token stores, persisting tokens between restarts and sharing them between workers.
+
+use crate::{auth::Token, error::Error};
+use async_trait::async_trait;
+use std::fmt::Debug;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Storage for the token of an auth method.
+/// The token is loaded before logging in, and saved after every login or refresh.
+#[async_trait]
+pub trait TokenStore: Debug + Send + Sync {
+    /// Loads the stored token, if there is one.
+    async fn load(&self) -> Result<Option<Token>, Error>;
+
+    /// Replaces the stored token.
+    async fn save(&self, token: &Token) -> Result<(), Error>;
+
+    /// Removes the stored token.
+    async fn clear(&self) -> Result<(), Error>;
+}
+
+/// A token store kept in memory.
+/// Clones share the same token, so several clients can use one store.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    token: Arc<Mutex<Option<Token>>>,
+}
+
+impl MemoryStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl TokenStore for MemoryStore {
+    async fn load(&self) -> Result<Option<Token>, Error> {
+        let token = self.token.lock().map_err(|_| "Store lock is poisoned")?;
+        Ok(token.clone())
+    }
+
+    async fn save(&self, token: &Token) -> Result<(), Error> {
+        let mut stored = self.token.lock().map_err(|_| "Store lock is poisoned")?;
+        *stored = Some(token.clone());
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<(), Error> {
+        let mut stored = self.token.lock().map_err(|_| "Store lock is poisoned")?;
+        *stored = None;
+        Ok(())
+    }
+}
+
+/// A token store saving the token as JSON in a file.
+/// A missing file means there is no token yet.
+///
+/// The file is read and written with blocking `std::fs` calls, stalling the executor thread for as long as they take.
+/// Tokens are small and only touched when logging in or refreshing, so this is rarely noticeable.
+///
+/// On unix the file is only readable and writable by its owner (mode 0600), since the tokens grant access to the account.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    /// Creates a store using the file at the given path.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        FileStore {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+#[async_trait]
+impl TokenStore for FileStore {
+    async fn load(&self) -> Result<Option<Token>, Error> {
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(Error::Custom(format!("Failed to read token file: {}", e))),
+        };
+        Ok(Some(serde_json::from_str(&contents)?))
+    }
+
+    async fn save(&self, token: &Token) -> Result<(), Error> {
+        let contents = serde_json::to_string_pretty(token)?;
+        // Written to a temporary file first, so other workers never read a half written token.
+        // Every write gets its own, so concurrent saves never write into the same file.
+        static WRITES: AtomicUsize = AtomicUsize::new(0);
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(
+            ".{}.{}.tmp",
+            std::process::id(),
+            WRITES.fetch_add(1, Ordering::Relaxed)
+        ));
+        let temporary = self.path.with_file_name(name);
+        write_private(&temporary, contents.as_bytes())
+            .and_then(|_| std::fs::rename(&temporary, &self.path))
+            .map_err(|e| {
+                let _ = std::fs::remove_file(&temporary);
+                Error::Custom(format!("Failed to write token file: {}", e))
+            })
+    }
+
+    async fn clear(&self) -> Result<(), Error> {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(Error::Custom(format!("Failed to remove token file: {}", e)))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Creates the file readable only by its owner, so other local users cannot read the tokens.
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents)
+}
@@ -0,0 +1,119 @@
+//! Two-factor authentication codes for accounts with 2FA enabled.
+
+use crate::error::Error;
+use hmac::{Hmac, Mac, NewMac};
+use sha1::Sha1;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A generator of time-based one-time passwords (RFC 6238), as used by reddit's two-factor authentication.
+#[derive(Clone)]
+pub struct Totp {
+    secret: Vec<u8>,
+    digits: u32,
+    step: u64,
+}
+
+impl Totp {
+    /// Creates a generator from the base32 encoded secret shown when enabling 2FA.
+    /// Spaces and lowercase letters are accepted.
+    pub fn new(secret: &str) -> Result<Self, Error> {
+        let cleaned: String = secret
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_uppercase();
+        let secret = base32::decode(base32::Alphabet::RFC4648 { padding: false }, &cleaned)
+            .ok_or("The TOTP secret is not valid base32")?;
+        Ok(Totp::from_bytes(&secret))
+    }
+
+    /// Creates a generator from the raw secret.
+    pub fn from_bytes(secret: &[u8]) -> Self {
+        Totp {
+            secret: secret.to_vec(),
+            digits: 6,
+            step: 30,
+        }
+    }
+
+    /// The amount of digits of each code, 6 by default.
+    /// RFC 6238 codes have between 6 and 8 digits, other amounts are refused.
+    pub fn digits(mut self, digits: u32) -> Result<Self, Error> {
+        if !(6..=8).contains(&digits) {
+            return Err(Error::Custom(format!(
+                "TOTP codes have 6 to 8 digits, not {}",
+                digits
+            )));
+        }
+        self.digits = digits;
+        Ok(self)
+    }
+
+    /// Returns the code for the current time.
+    pub fn code(&self) -> String {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.code_at(now)
+    }
+
+    /// Returns the code for the given time in seconds since the unix epoch.
+    pub fn code_at(&self, timestamp: u64) -> String {
+        let counter = timestamp / self.step;
+        let mut mac =
+            Hmac::<Sha1>::new_varkey(&self.secret).expect("HMAC accepts keys of any size");
+        mac.update(&counter.to_be_bytes());
+        let hash = mac.finalize().into_bytes();
+
+        // Dynamic truncation, taking four bytes at the offset given by the last nibble.
+        let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+        let binary = u32::from_be_bytes([
+            hash[offset] & 0x7f,
+            hash[offset + 1],
+            hash[offset + 2],
+            hash[offset + 3],
+        ]);
+        let code = binary % 10u32.pow(self.digits);
+        format!("{:0width$}", code, width = self.digits as usize)
+    }
+}
+
+impl fmt::Debug for Totp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Totp")
+            .field("digits", &self.digits)
+            .field("step", &self.step)
+            .finish()
+    }
+}
+
+/// The source of the two-factor code sent along with the password.
+#[derive(Clone)]
+pub enum TwoFactor {
+    /// Codes generated locally from the TOTP secret.
+    Totp(Totp),
+    /// Codes returned by a callback, for example from a hardware token or another service.
+    Callback(Arc<dyn Fn() -> String + Send + Sync>),
+}
+
+impl TwoFactor {
+    /// Returns the current code.
+    pub fn code(&self) -> String {
+        match self {
+            TwoFactor::Totp(totp) => totp.code(),
+            TwoFactor::Callback(callback) => callback(),
+        }
+    }
+}
+
+impl fmt::Debug for TwoFactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TwoFactor::Totp(totp) => f.debug_tuple("Totp").field(totp).finish(),
+            TwoFactor::Callback(_) => f.debug_tuple("Callback").finish(),
+        }
+    }
+}
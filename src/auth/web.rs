@@ -0,0 +1,163 @@
+//! The authorization code authentication method, acting on behalf of other reddit users.
+
+use crate::{
+    auth::{request_token, scope::Scope, store::TokenStore, Auth, Token, TokenCache},
+    client::{endpoints::Endpoints, transport::HttpTransport},
+    error::Error,
+};
+use async_trait::async_trait;
+use reqwest::Url;
+use std::sync::Mutex;
+
+/// The authentication method for applications acting on behalf of other users.
+/// It requires the use of the "web app" account type.
+#[derive(Debug)]
+pub struct WebAuth {
+    /// The application ID.
+    pub client_id: String,
+    /// The secret OAuth application key.
+    pub secret_key: String,
+    /// The redirect URI registered for the application.
+    pub redirect_uri: String,
+    code: Mutex<Option<String>>,
+    token_cache: TokenCache,
+}
+
+impl WebAuth {
+    /// Builds the URL the user has to visit to authorize the application.
+    /// Reddit redirects back to `redirect_uri` with the given `state` and a `code` to pass to `WebAuth::new`.
+    /// Permanent authorizations come with a refresh token, temporary ones expire after an hour.
+    /// The URL points to the `www` endpoint of the given endpoints.
+    pub fn authorize_url(
+        endpoints: &Endpoints,
+        client_id: &str,
+        redirect_uri: &str,
+        state: &str,
+        scopes: &[Scope],
+        permanent: bool,
+    ) -> Result<Url, Error> {
+        let duration = if permanent { "permanent" } else { "temporary" };
+        Url::parse_with_params(
+            &endpoints.authorize_url(),
+            &[
+                ("client_id", client_id),
+                ("response_type", "code"),
+                ("state", state),
+                ("redirect_uri", redirect_uri),
+                ("duration", duration),
+                ("scope", &Scope::join(scopes)),
+            ],
+        )
+        .map_err(|e| Error::Custom(e.to_string()))
+    }
+
+    /// Builder for the WebAuth method, using the code reddit returned to the redirect URI.
+    pub async fn new(client_id: &str, secret_key: &str, redirect_uri: &str, code: &str) -> Self {
+        WebAuth {
+            client_id: client_id.to_string(),
+            secret_key: secret_key.to_string(),
+            redirect_uri: redirect_uri.to_string(),
+            code: Mutex::new(Some(code.to_string())),
+            token_cache: TokenCache::new(),
+        }
+    }
+
+    /// Builder for the WebAuth method, using a refresh token obtained earlier.
+    pub async fn from_refresh_token(
+        client_id: &str,
+        secret_key: &str,
+        redirect_uri: &str,
+        refresh_token: &str,
+    ) -> Self {
+        WebAuth {
+            client_id: client_id.to_string(),
+            secret_key: secret_key.to_string(),
+            redirect_uri: redirect_uri.to_string(),
+            code: Mutex::new(None),
+            token_cache: TokenCache::with_refresh_token(refresh_token),
+        }
+    }
+
+    /// Returns the scopes the user granted, available after logging in.
+    pub async fn scopes(&self) -> Vec<Scope> {
+        self.token_cache
+            .get()
+            .await
+            .map(|t| t.scopes)
+            .unwrap_or_default()
+    }
+
+    /// Returns the refresh token, available after logging in with a permanent authorization.
+    pub async fn refresh_token(&self) -> Option<String> {
+        self.token_cache.get().await.and_then(|t| t.refresh_token)
+    }
+
+    /// Loads and saves the token using the given store, resuming the session of a previous run if possible.
+    pub fn with_store<S: TokenStore + 'static>(mut self, store: S) -> Self {
+        self.token_cache.set_store(store);
+        self
+    }
+}
+
+#[async_trait]
+impl Auth for WebAuth {
+    async fn login(
+        &self,
+        transport: &dyn HttpTransport,
+        endpoints: &Endpoints,
+        current: Option<&Token>,
+    ) -> Result<Token, Error> {
+        let code = self
+            .code
+            .lock()
+            .map_err(|_| "Code lock is poisoned")?
+            .clone();
+        let refresh_token = current.and_then(|t| t.refresh_token.as_deref());
+        let form: Vec<(&str, &str)> = match (&code, refresh_token) {
+            (Some(code), _) => vec![
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", &self.redirect_uri),
+            ],
+            (None, Some(refresh_token)) => vec![
+                ("grant_type", "refresh_token"),
+                ("refresh_token", refresh_token),
+            ],
+            (None, None) => {
+                return Err(Error::Custom(
+                    "No code or refresh token available, the user has to authorize again".into(),
+                ))
+            }
+        };
+
+        let token = request_token(
+            transport,
+            endpoints,
+            &self.client_id,
+            &self.secret_key,
+            &form,
+            current,
+        )
+        .await?;
+        // A code can only be exchanged once, afterwards the refresh token is used.
+        // It is only dropped after the exchange worked, so a failed attempt can be repeated.
+        if code.is_some() {
+            *self.code.lock().map_err(|_| "Code lock is poisoned")? = None;
+        }
+        Ok(token)
+    }
+
+    async fn logout(
+        &self,
+        transport: &dyn HttpTransport,
+        endpoints: &Endpoints,
+    ) -> Result<(), Error> {
+        self.token_cache
+            .logout(transport, endpoints, &self.client_id, &self.secret_key)
+            .await
+    }
+
+    fn token_cache(&self) -> &TokenCache {
+        &self.token_cache
+    }
+}
@@ -1,43 +1,82 @@
 //! Client is used to handle requests through the reddit API.
 
+pub mod builder;
+pub mod cassette;
+pub mod endpoints;
+pub mod ratelimit;
+pub mod retry;
 pub(crate) mod route;
+pub mod transport;
 
 use crate::{
-    auth::Auth,
-    client::route::Route,
-    error::Error,
-    model::misc::Params,
+    auth::{Auth, Token},
+    client::{
+        builder::ClientBuilder,
+        endpoints::Endpoints,
+        ratelimit::{RateLimit, RateLimiter},
+        retry::RetryPolicy,
+        route::Route,
+        transport::{HttpRequest, HttpResponse, HttpTransport},
+    },
+    error::{ApiError, Error},
+    model::misc::{CommentOptions, Fullname, ListingOptions, Params},
     model::{
-        comment::Comment, link::Link, listing::Listing, subreddit::Subreddit, thing::Thing,
+        comment::Comment,
+        commenttree::CommentTree,
+        link::Link,
+        listing::Listing,
+        message::Message,
+        more::{assemble, flatten, More},
+        subreddit::Subreddit,
+        thing::Thing,
         user::User,
     },
+    stream::{InboxStream, ListingStream, PollOptions, PollStream},
 };
+use futures::TryStreamExt;
 use futures_timer::Delay;
-use reqwest::Response;
-use serde_json::{Map, Value};
-use std::convert::TryInto;
-use std::time::Duration;
+use http::{Method, StatusCode};
+use serde_json::Value;
+use std::collections::{HashSet, VecDeque};
+use std::convert::{TryFrom, TryInto};
+
+/// The amount of items reddit returns in a listing when no limit is given.
+const DEFAULT_LIMIT: u64 = 25;
 
 /// The client handling the requests.
 #[derive(Debug)]
 pub struct Client<T: Auth> {
-    /// Access token used for all requests.
-    access_token: String,
-    /// The auth model chosen for connecting to reddit.
+    /// The auth model chosen for connecting to reddit, holding the access token.
     auth: T,
-    /// User agent representing the client sending the requests.
-    user_agent: String,
+    /// The transport sending the requests.
+    transport: Box<dyn HttpTransport>,
+    /// The hosts the requests are sent to.
+    endpoints: Endpoints,
+    /// Spaces out the requests according to the rate limit.
+    rate_limiter: RateLimiter,
+    /// Decides which failed requests are sent again.
+    retry: RetryPolicy,
 }
 
 impl<T: Auth + Send + Sync> Client<T> {
     /// The function used to construct a new client.
+    /// It logs in right away, so invalid credentials are noticed early.
+    /// Use `ClientBuilder` for configuring timeouts, a proxy or the HTTP client.
     pub async fn new(user_agent: &str, auth_method: T) -> Result<Self, Error> {
-        let access_token = auth_method.login().await?;
-        Ok(Client {
-            auth: auth_method,
-            access_token,
-            user_agent: user_agent.to_string(),
-        })
+        ClientBuilder::new(user_agent).build(auth_method).await
+    }
+
+    /// Returns the rate limit budget reddit reported with the last response.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limiter.current()
+    }
+
+    /// Revokes the tokens of this client and clears its token store.
+    /// Any request made afterwards fails with `Error::LoggedOut`.
+    pub async fn logout(&self) -> Result<(), Error> {
+        self.auth
+            .logout(self.transport.as_ref(), &self.endpoints)
+            .await
     }
 
     /// Retrieves the user information given a username.
@@ -45,7 +84,7 @@ impl<T: Auth + Send + Sync> Client<T> {
         let response = self
             .get(Route::UserAbout(username.into()), &Params::new())
             .await?;
-        let body = response.text().await?;
+        let body = response.body;
         let thing: Thing = serde_json::from_str(&body)?;
         let user: User = Thing::try_into(thing)?;
         Ok(user)
@@ -56,7 +95,7 @@ impl<T: Auth + Send + Sync> Client<T> {
         let response = self
             .get(Route::SubredditAbout(subreddit.into()), &Params::new())
             .await?;
-        let body = response.text().await?;
+        let body = response.body;
         let thing: Thing = serde_json::from_str(&body)?;
         let user: Subreddit = Thing::try_into(thing)?;
         Ok(user)
@@ -70,11 +109,14 @@ impl<T: Auth + Send + Sync> Client<T> {
                 &Params::new().add("id", &format!("t1_{}", comment)),
             )
             .await?;
-        let body = response.text().await?;
+        let body = response.body;
         let thing: Thing = serde_json::from_str(&body)?;
         let listing: Listing = Thing::try_into(thing)?;
-        let mut comments: Vec<Comment> = Listing::try_into(listing)?;
-        let comment = comments.remove(0);
+        let comments: Vec<Comment> = Listing::try_into(listing)?;
+        let comment = comments
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Custom(format!("No comment with the ID {}", comment)))?;
         Ok(comment)
     }
 
@@ -86,11 +128,14 @@ impl<T: Auth + Send + Sync> Client<T> {
                 &Params::new().add("id", &format!("t3_{}", link)),
             )
             .await?;
-        let body = response.text().await?;
+        let body = response.body;
         let thing: Thing = serde_json::from_str(&body)?;
         let listing: Listing = Thing::try_into(thing)?;
-        let mut links: Vec<Link> = Listing::try_into(listing)?;
-        let link = links.remove(0);
+        let links: Vec<Link> = Listing::try_into(listing)?;
+        let link = links
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Custom(format!("No link with the ID {}", link)))?;
         Ok(link)
     }
 
@@ -102,91 +147,261 @@ impl<T: Auth + Send + Sync> Client<T> {
                 &Params::new().add("user", username),
             )
             .await?;
-        let body = response.text().await?;
+        let body = response.body;
         let available: bool = serde_json::from_str(&body)?;
         Ok(available)
     }
 
-    pub(crate) async fn get(&self, route: Route, params: &Params) -> Result<Response, Error> {
-        let response = self
-            .auth
-            .get(route, &self.access_token, &self.user_agent, params)
-            .await?;
+    /// Expands a placeholder belonging to the given link, returning the comments it stood for.
+    /// Placeholders found along the way are expanded as well, so the returned comments are fully loaded.
+    pub async fn more_children(
+        &self,
+        link_id: &Fullname,
+        more: &More,
+        options: &CommentOptions,
+    ) -> Result<Vec<Comment>, Error> {
+        self.load_thread(
+            link_id,
+            vec![Thing::More(more.clone())],
+            &more.parent_id,
+            options,
+        )
+        .await
+    }
 
-        self.handle_ratelimit(&response).await?;
-        Ok(response)
+    /// Returns a never-ending stream of new posts in the given subreddits.
+    /// Multiple subreddits are combined, so `&["rust", "programming"]` polls `r/rust+programming`.
+    pub fn submission_stream(
+        &self,
+        subreddits: &[&str],
+        options: PollOptions,
+    ) -> PollStream<'_, Link> {
+        PollStream::new(
+            self,
+            Route::SubredditNew(subreddits.join("+")),
+            Params::new(),
+            options,
+        )
     }
 
-    pub(crate) async fn post(&self, route: Route, params: &Params) -> Result<Response, Error> {
-        let response = self
-            .auth
-            .post(route, &self.access_token, &self.user_agent, params)
-            .await?;
+    /// Returns a never-ending stream of new comments in the given subreddits.
+    /// Multiple subreddits are combined the same way as in `submission_stream`.
+    pub fn comment_stream(
+        &self,
+        subreddits: &[&str],
+        options: PollOptions,
+    ) -> PollStream<'_, Comment> {
+        PollStream::new(
+            self,
+            Route::SubredditComments(subreddits.join("+")),
+            Params::new(),
+            options,
+        )
+    }
 
-        self.handle_ratelimit(&response).await?;
-        Ok(response)
+    /// Returns the messages in the inbox, including comment replies and username mentions.
+    pub fn inbox(&self, options: &ListingOptions) -> ListingStream<'_, Message> {
+        ListingStream::new(self, Route::MessageInbox, options.clone())
     }
 
-    pub(crate) async fn delete(&self, route: Route) -> Result<Response, Error> {
-        let response = self
-            .auth
-            .delete(route, &self.access_token, &self.user_agent)
-            .await?;
+    /// Returns the unread messages in the inbox, without marking them as read.
+    pub fn unread(&self, options: &ListingOptions) -> ListingStream<'_, Message> {
+        ListingStream::new(
+            self,
+            Route::MessageUnread,
+            options.clone().param("mark", "false"),
+        )
+    }
 
-        self.handle_ratelimit(&response).await?;
-        Ok(response)
+    /// Returns a never-ending stream of unread inbox items, each returned only once.
+    /// With `mark_read` set, an item is marked as read once the next one is requested, so items whose handling
+    /// was interrupted come back. If marking fails the error is returned and the item is tried again with the next one.
+    /// The last item is marked with `InboxStream::mark_read`.
+    pub fn inbox_stream(&self, mark_read: bool, options: PollOptions) -> InboxStream<'_> {
+        InboxStream::new(self, mark_read, options)
     }
 
-    pub(crate) async fn put(&self, route: Route, params: &Params) -> Result<Response, Error> {
-        let response = self
-            .auth
-            .put(route, &self.access_token, &self.user_agent, params)
+    /// Returns the messages sent by the current user.
+    pub fn sent(&self, options: &ListingOptions) -> ListingStream<'_, Message> {
+        ListingStream::new(self, Route::MessageSent, options.clone())
+    }
+
+    /// Returns the comments mentioning the current user.
+    pub fn mentions(&self, options: &ListingOptions) -> ListingStream<'_, Message> {
+        ListingStream::new(self, Route::MessageMentions, options.clone())
+    }
+
+    /// Returns the replies to comments made by the current user.
+    pub fn comment_replies(&self, options: &ListingOptions) -> ListingStream<'_, Message> {
+        ListingStream::new(self, Route::MessageCommentReplies, options.clone())
+    }
+
+    /// Sends a private message to a user, or to the moderators of a subreddit if `to` is `/r/subreddit`.
+    pub async fn compose(&self, to: &str, subject: &str, body: &str) -> Result<(), Error> {
+        self.post(
+            Route::Compose,
+            &Params::new()
+                .add("to", to)
+                .add("subject", subject)
+                .add("text", body)
+                .add("api_type", "json"),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Marks every message in the inbox as read.
+    pub async fn read_all_messages(&self) -> Result<(), Error> {
+        self.post(Route::ReadAllMessages, &Params::new()).await?;
+        Ok(())
+    }
+
+    pub(crate) async fn get(&self, route: Route, params: &Params) -> Result<HttpResponse, Error> {
+        self.request(Method::GET, route, params).await
+    }
+
+    /// Sends an action, returning the JSON response after checking it for errors.
+    pub(crate) async fn post(&self, route: Route, params: &Params) -> Result<Value, Error> {
+        let response = self.request(Method::POST, route, params).await?;
+        let body = response.body;
+        if body.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        let value: Value = serde_json::from_str(&body)?;
+        match ApiError::from_json(&value) {
+            Some(e) => Err(Error::Api(e)),
+            None => Ok(value),
+        }
+    }
+
+    pub(crate) async fn delete(&self, route: Route) -> Result<HttpResponse, Error> {
+        self.request(Method::DELETE, route, &Params::new()).await
+    }
+
+    pub(crate) async fn put(&self, route: Route, params: &Params) -> Result<HttpResponse, Error> {
+        self.request(Method::PUT, route, params).await
+    }
+
+    async fn token(&self) -> Result<Token, Error> {
+        self.auth
+            .token_cache()
+            .token(&self.auth, self.transport.as_ref(), &self.endpoints)
+            .await
+    }
+
+    /// Sends a request with the current access token.
+    /// If reddit rejects the token, it is refreshed and the request is sent once more.
+    async fn request(
+        &self,
+        method: Method,
+        route: Route,
+        params: &Params,
+    ) -> Result<HttpResponse, Error> {
+        if self.auth.read_only() && method != Method::GET {
+            return Err(Error::ReadOnly);
+        }
+
+        let token = self.token().await?;
+        let scopes = route.scopes();
+        if !scopes.is_empty() && !scopes.iter().any(|scope| token.has_scope(scope)) {
+            return Err(Error::MissingScope(scopes[0].clone()));
+        }
+
+        let mut response = self
+            .send(method.clone(), route.clone(), &token.access_token, params)
             .await?;
+        if response.status == StatusCode::UNAUTHORIZED {
+            self.auth
+                .token_cache()
+                .invalidate(&token.access_token)
+                .await;
+            let token = self.token().await?;
+            response = self
+                .send(method, route, &token.access_token, params)
+                .await?;
+        }
 
-        self.handle_ratelimit(&response).await?;
+        if !response.status.is_success() {
+            let status = response.status.as_u16();
+            return Err(serde_json::from_str(&response.body)
+                .ok()
+                .and_then(|value| ApiError::from_json(&value))
+                .map_or(Error::Status(status), Error::Api));
+        }
         Ok(response)
     }
 
-    async fn handle_ratelimit(&self, response: &Response) -> Result<(), Error> {
-        let headers = response.headers();
-        let _used: u64 = headers
-            .get("x-ratelimit-used")
-            .ok_or_else(|| Error::MissingHeader("x-ratelimit-used".to_string()))?
-            .to_str()?
-            .parse()?;
-
-        let remaining: u64 = headers
-            .get("x-ratelimit-remaining")
-            .ok_or_else(|| Error::MissingHeader("x-ratelimit-remaining".to_string()))?
-            .to_str()?
-            .parse::<f64>()?
-            .to_bits();
-
-        let reset: u64 = headers
-            .get("x-ratelimit-reset")
-            .ok_or_else(|| Error::MissingHeader("x-ratelimit-reset".to_string()))?
-            .to_str()?
-            .parse()?;
-
-        if remaining < 1 {
-            let _ = Delay::new(Duration::from_secs(reset)).await;
-        }
+    /// Sends the request, retrying transient failures according to the retry policy.
+    async fn send(
+        &self,
+        method: Method,
+        route: Route,
+        token: &str,
+        params: &Params,
+    ) -> Result<HttpResponse, Error> {
+        let mut url = self.endpoints.api_url(&format!("{}?raw_json=1", route));
+        // POST and PUT send their parameters as a form, the way reddit expects them.
+        let request = if method == Method::POST || method == Method::PUT {
+            HttpRequest::new(method.clone(), &url).form(params)?
+        } else {
+            let query = serde_urlencoded::to_string(params)
+                .map_err(|e| Error::Custom(format!("Invalid parameters: {}", e)))?;
+            if !query.is_empty() {
+                url = format!("{}&{}", url, query);
+            }
+            HttpRequest::new(method.clone(), &url)
+        };
+        let request = request.bearer_auth(token)?;
 
-        Ok(())
+        let mut attempt = 1;
+        loop {
+            self.rate_limiter.acquire().await?;
+            let result = self.transport.send(request.clone()).await;
+
+            let delay = match &result {
+                Ok(response) => {
+                    self.rate_limiter.update(&response.headers)?;
+                    if !self.retry.retries_status(response.status) {
+                        return result;
+                    }
+                    self.retry.delay(attempt, Some(&response.headers))
+                }
+                Err(e) if self.retry.retries_error(e) => self.retry.delay(attempt, None),
+                Err(_) => return result,
+            };
+            let delay = match delay {
+                Some(delay) if self.retry.allows(&method, attempt) => delay,
+                _ => return result,
+            };
+            Delay::new(delay).await;
+            attempt += 1;
+        }
     }
 
-    pub(crate) async fn get_posts(&self, route: Route) -> Result<Vec<Link>, Error> {
-        let response = self.get(route, &Params::new()).await?;
-        let body = response.text().await?;
+    pub(crate) async fn get_listing(
+        &self,
+        route: Route,
+        params: &Params,
+    ) -> Result<Listing, Error> {
+        let response = self.get(route, params).await?;
+        let body = response.body;
         let thing: Thing = serde_json::from_str(&body)?;
         let listing: Listing = Thing::try_into(thing)?;
-        let mut links: Vec<Link> = Vec::new();
-        for x in &listing.children {
-            let y = x.clone();
-            let link: Link = Thing::try_into(y)?;
-            links.push(link);
-        }
-        Ok(links)
+        Ok(listing)
+    }
+
+    /// Collects the items of a listing, a single page of reddit's default size unless the options ask for more.
+    pub(crate) async fn get_items<R>(
+        &self,
+        route: Route,
+        options: &ListingOptions,
+    ) -> Result<Vec<R>, Error>
+    where
+        R: TryFrom<Thing, Error = Error> + Send,
+    {
+        let mut options = options.clone();
+        options.limit = Some(options.limit.unwrap_or(DEFAULT_LIMIT));
+        ListingStream::new(self, route, options).try_collect().await
     }
 
     pub(crate) async fn replies(
@@ -197,7 +412,7 @@ impl<T: Auth + Send + Sync> Client<T> {
         let path = format!("/comments/{}/_/{}", link_id, name);
         let params = Params::new().add("context", "0").add("limit", "100");
         let response = self.get(Route::Custom(path), &params).await?;
-        let body = response.text().await?;
+        let body = response.body;
         let mut listings: Vec<Thing> = serde_json::from_str(&body)?;
         let listing: Listing = Thing::try_into(listings.remove(1))?;
         let mut comments: Vec<Comment> = Listing::try_into(listing)?;
@@ -210,7 +425,119 @@ impl<T: Auth + Send + Sync> Client<T> {
         Ok(replies3)
     }
 
-    pub(crate) async fn submit_comment(&self, thing_id: &str, body: &str) -> Result<(), Error> {
+    /// Returns the top-level Things in the comment section of a link, comments and placeholders alike.
+    pub(crate) async fn link_comments(
+        &self,
+        link_id: &str,
+        options: &CommentOptions,
+    ) -> Result<Vec<Thing>, Error> {
+        let response = self
+            .get(Route::Submission(link_id.into()), &options.params())
+            .await?;
+        let body = response.body;
+        let mut listings: Vec<Thing> = serde_json::from_str(&body)?;
+        if listings.len() < 2 {
+            return Err(Error::Custom("Missing comment listing".into()));
+        }
+        let listing: Listing = Thing::try_into(listings.remove(1))?;
+        Ok(listing.children)
+    }
+
+    /// Returns the comment section of a link as a tree.
+    pub(crate) async fn comment_tree(
+        &self,
+        link_id: &str,
+        options: &CommentOptions,
+    ) -> Result<CommentTree, Error> {
+        let response = self
+            .get(Route::Submission(link_id.into()), &options.params())
+            .await?;
+        let body = response.body;
+        let listings: Vec<Thing> = serde_json::from_str(&body)?;
+        CommentTree::try_from(listings)
+    }
+
+    /// Expands every placeholder in the given Things and returns the fully loaded children of `root`.
+    pub(crate) async fn load_thread(
+        &self,
+        link_id: &Fullname,
+        things: Vec<Thing>,
+        root: &Fullname,
+        options: &CommentOptions,
+    ) -> Result<Vec<Comment>, Error> {
+        let mut comments: Vec<Comment> = Vec::new();
+        let mut pending: VecDeque<More> = VecDeque::new();
+        // reddit can hand out a placeholder again, remembering what was expanded keeps the loop finite.
+        let mut expanded: HashSet<Fullname> = HashSet::new();
+        let mut requested: HashSet<String> = HashSet::new();
+        flatten(things, &mut comments, &mut pending);
+        while let Some(mut more) = pending.pop_front() {
+            // "Continue this thread" placeholders all share one name, they are told apart by their parent.
+            let key = if more.children.is_empty() {
+                more.parent_id.clone()
+            } else {
+                more.name.clone()
+            };
+            if !expanded.insert(key) {
+                continue;
+            }
+            if !more.children.is_empty() {
+                more.children.retain(|id| requested.insert(id.clone()));
+                if more.children.is_empty() {
+                    continue;
+                }
+            }
+            let things = self.fetch_more(link_id, &more, options).await?;
+            flatten(things, &mut comments, &mut pending);
+        }
+        Ok(assemble(comments, root))
+    }
+
+    async fn fetch_more(
+        &self,
+        link_id: &Fullname,
+        more: &More,
+        options: &CommentOptions,
+    ) -> Result<Vec<Thing>, Error> {
+        // "Continue this thread" placeholders carry no IDs, the parent has to be loaded instead.
+        if more.children.is_empty() {
+            if more.parent_id.prefix() != "t1" {
+                return Ok(Vec::new());
+            }
+            let route = Route::SubmissionComment(link_id.name(), more.parent_id.name());
+            let response = self.get(route, &options.thread_params()).await?;
+            let body = response.body;
+            let mut listings: Vec<Thing> = serde_json::from_str(&body)?;
+            if listings.len() < 2 {
+                return Err(Error::Custom("Missing comment listing".into()));
+            }
+            let listing: Listing = Thing::try_into(listings.remove(1))?;
+            return Ok(listing.children);
+        }
+
+        let mut things: Vec<Thing> = Vec::new();
+        for chunk in more.children.chunks(100) {
+            let params = options
+                .more_params()
+                .add("api_type", "json")
+                .add("link_id", link_id.as_ref())
+                .add("children", &chunk.join(","));
+            let response = self.get(Route::MoreChildren, &params).await?;
+            let body = response.body;
+            let parsed: Value = serde_json::from_str(&body)?;
+            let children = parsed
+                .get("json")
+                .and_then(|json| json.get("data"))
+                .and_then(|data| data.get("things"))
+                .ok_or("Invalid morechildren response")?;
+            let mut batch: Vec<Thing> = serde_json::from_value(children.clone())?;
+            things.append(&mut batch);
+        }
+        Ok(things)
+    }
+
+    /// Posts a comment, returning the created Thing.
+    pub(crate) async fn submit_comment(&self, thing_id: &str, body: &str) -> Result<Thing, Error> {
         let response = self
             .post(
                 Route::Comment,
@@ -220,23 +547,29 @@ impl<T: Auth + Send + Sync> Client<T> {
                     .add("api_type", "json"),
             )
             .await?;
-        let body = response.text().await?;
-        let parsed: Map<String, Value> = serde_json::from_str(&body)?;
-        let json = parsed
-            .get("json")
-            .ok_or_else(|| "Invalid response 1")?
-            .as_object()
-            .ok_or_else(|| "Invalid response 2")?;
-        let errors: Vec<Value> = json
-            .get("errors")
-            .ok_or_else(|| "Invalid response 3")?
-            .as_array()
-            .ok_or_else(|| "Invalid response 4")?
-            .clone();
-        if errors.is_empty() {
-            Ok(())
-        } else {
-            Err(Error::Custom("Invalid response 5".into()))
-        }
+        created_thing(response)
     }
+
+    /// Edits the text of a comment or self post, returning the edited Thing.
+    pub(crate) async fn edit_text(&self, thing_id: &str, body: &str) -> Result<Thing, Error> {
+        let response = self
+            .post(
+                Route::EditUserText,
+                &Params::new()
+                    .add("thing_id", thing_id)
+                    .add("text", body)
+                    .add("api_type", "json"),
+            )
+            .await?;
+        created_thing(response)
+    }
+}
+
+/// Takes the Thing out of the `json.data.things` field of a response.
+fn created_thing(mut response: Value) -> Result<Thing, Error> {
+    let thing = response
+        .pointer_mut("/json/data/things/0")
+        .ok_or("No `things` field in response")?
+        .take();
+    Ok(serde_json::from_value(thing)?)
 }
@@ -0,0 +1,146 @@
+//! The builder used to configure a client before logging in.
+
+use crate::{
+    auth::Auth,
+    client::{
+        endpoints::Endpoints,
+        ratelimit::RateLimiter,
+        retry::RetryPolicy,
+        transport::{HttpTransport, ReqwestTransport, UserAgentTransport},
+        Client,
+    },
+    error::Error,
+};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client as HttpClient, Proxy};
+use std::time::Duration;
+
+/// Formats a user agent the way reddit asks for it, such as `linux:my-bot:v1.0.0 (by /u/username)`.
+pub fn user_agent(platform: &str, app_id: &str, version: &str, username: &str) -> String {
+    format!(
+        "{}:{}:{} (by /u/{})",
+        platform,
+        app_id,
+        version,
+        username.trim_start_matches("/u/").trim_start_matches("u/")
+    )
+}
+
+/// Configures the HTTP settings of a client.
+#[derive(Debug)]
+pub struct ClientBuilder {
+    user_agent: String,
+    endpoints: Endpoints,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    proxy: Option<String>,
+    headers: Vec<(String, String)>,
+    transport: Option<Box<dyn HttpTransport>>,
+    retry: RetryPolicy,
+}
+
+impl ClientBuilder {
+    /// Creates a builder with the given user agent, see `user_agent` for the format reddit expects.
+    pub fn new(user_agent: &str) -> Self {
+        ClientBuilder {
+            user_agent: user_agent.to_string(),
+            endpoints: Endpoints::default(),
+            timeout: None,
+            connect_timeout: None,
+            proxy: None,
+            headers: Vec::new(),
+            transport: None,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Sends the requests to the given endpoints instead of reddit.
+    pub fn endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    /// How long a request may take in total, from connecting until the whole response is read.
+    /// This is reqwest's total `timeout`, there is no separate limit on reading the response.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// How long connecting to reddit may take.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sends all requests through the proxy at the given URL.
+    pub fn proxy(mut self, url: &str) -> Self {
+        self.proxy = Some(url.to_string());
+        self
+    }
+
+    /// Adds a header sent along with every request.
+    pub fn default_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Uses an already configured HTTP client, which can be shared with other parts of the application.
+    /// The timeouts, proxy and default headers of this builder are ignored in that case.
+    pub fn http_client(self, http_client: HttpClient) -> Self {
+        self.transport(ReqwestTransport::with_client(http_client))
+    }
+
+    /// Sends the requests through a custom transport instead of reqwest.
+    /// The timeouts, proxy and default headers of this builder are ignored in that case, the user agent is still added.
+    pub fn transport<H: HttpTransport + 'static>(mut self, transport: H) -> Self {
+        self.transport = Some(Box::new(transport));
+        self
+    }
+
+    /// Which failed requests are sent again, see `RetryPolicy` for the defaults.
+    pub fn retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Builds the client and logs in with the given auth method.
+    pub async fn build<T: Auth + Send + Sync>(self, auth_method: T) -> Result<Client<T>, Error> {
+        let transport = match self.transport {
+            Some(transport) => transport,
+            None => {
+                let mut builder = HttpClient::builder();
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                if let Some(proxy) = &self.proxy {
+                    builder = builder.proxy(Proxy::all(proxy)?);
+                }
+                let mut headers = HeaderMap::new();
+                for (name, value) in &self.headers {
+                    let name = HeaderName::from_bytes(name.as_bytes())
+                        .map_err(|_| Error::Custom(format!("Invalid header name: {}", name)))?;
+                    let value = HeaderValue::from_str(value)
+                        .map_err(|_| Error::Custom(format!("Invalid header value: {}", value)))?;
+                    headers.insert(name, value);
+                }
+                Box::new(ReqwestTransport::with_client(
+                    builder.default_headers(headers).build()?,
+                ))
+            }
+        };
+
+        let client = Client {
+            auth: auth_method,
+            transport: Box::new(UserAgentTransport::new(transport, &self.user_agent)?),
+            endpoints: self.endpoints,
+            rate_limiter: RateLimiter::default(),
+            retry: self.retry,
+        };
+        client.token().await?;
+        Ok(client)
+    }
+}
@@ -0,0 +1,239 @@
+//! Recording reddit traffic to a file and replaying it offline, for deterministic tests.
+
+use crate::{
+    client::transport::{HttpRequest, HttpResponse, HttpTransport},
+    error::Error,
+};
+use async_trait::async_trait;
+use http::header::{HeaderMap, HeaderName, HeaderValue, SET_COOKIE};
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::convert::TryFrom;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The value replacing secrets in a cassette.
+pub const REDACTED: &str = "REDACTED";
+
+/// Request parameters that are never written to a cassette.
+const SECRET_PARAMS: &[&str] = &[
+    "password",
+    "token",
+    "refresh_token",
+    "code",
+    "client_secret",
+];
+
+/// Response fields that are never written to a cassette.
+const SECRET_FIELDS: &[&str] = &["access_token", "refresh_token"];
+
+/// A recorded request and the response reddit sent to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Interaction {
+    method: String,
+    path: String,
+    params: Vec<(String, String)>,
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Interaction {
+    fn matches(&self, method: &str, path: &str, params: &[(String, String)]) -> bool {
+        self.method == method && self.path == path && self.params == params
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Tape {
+    interactions: Vec<Interaction>,
+}
+
+/// A transport recording every request and response to a cassette file, or replaying them from it.
+///
+/// Requests are matched on their method, route and parameters, ignoring the host,
+/// so a cassette recorded against reddit can be replayed with any `Endpoints`.
+/// Passwords, authorization codes and tokens are replaced by `REDACTED` and request headers are not recorded.
+pub struct Cassette {
+    path: PathBuf,
+    /// The transport requests are recorded from, missing when replaying.
+    transport: Option<Box<dyn HttpTransport>>,
+    tape: Mutex<Tape>,
+    played: Mutex<Vec<bool>>,
+}
+
+impl fmt::Debug for Cassette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cassette")
+            .field("path", &self.path)
+            .field("transport", &self.transport)
+            .finish()
+    }
+}
+
+impl Cassette {
+    /// Sends the requests through the given transport, recording them to a new cassette at the path.
+    /// The file is rewritten after every response, so it is complete even if the program stops early.
+    /// Writing it blocks the executor thread, which is fine for tests and recording sessions but not meant for production.
+    pub fn record<P: AsRef<Path>, H: HttpTransport + 'static>(path: P, transport: H) -> Self {
+        Cassette {
+            path: path.as_ref().to_path_buf(),
+            transport: Some(Box::new(transport)),
+            tape: Mutex::new(Tape::default()),
+            played: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Answers the requests from the cassette at the path, without sending anything.
+    /// Each recorded response is served once in the recorded order, after which the last one
+    /// matching a request is repeated, so polling can run for longer than it was recorded.
+    pub fn replay<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let contents = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| Error::Custom(format!("Failed to read cassette: {}", e)))?;
+        let tape: Tape = serde_json::from_str(&contents)?;
+        Ok(Cassette {
+            path: path.as_ref().to_path_buf(),
+            transport: None,
+            played: Mutex::new(vec![false; tape.interactions.len()]),
+            tape: Mutex::new(tape),
+        })
+    }
+
+    async fn record_request(
+        &self,
+        transport: &dyn HttpTransport,
+        request: HttpRequest,
+    ) -> Result<HttpResponse, Error> {
+        let (method, path, params) = key(&request);
+        let response = transport.send(request).await?;
+
+        let headers = response
+            .headers
+            .iter()
+            .filter(|(name, _)| **name != SET_COOKIE)
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let interaction = Interaction {
+            method,
+            path,
+            params,
+            status: response.status.as_u16(),
+            headers,
+            body: redact_body(&response.body),
+        };
+
+        // The file is written while the tape is locked, so concurrent requests never save an outdated tape.
+        // It goes to a temporary file first, so an interrupted recording never leaves a broken cassette.
+        let mut tape = self.tape.lock().map_err(|_| "Cassette lock is poisoned")?;
+        tape.interactions.push(interaction);
+        let contents = serde_json::to_string_pretty(&*tape)?;
+        let mut temporary = self.path.file_name().unwrap_or_default().to_os_string();
+        temporary.push(".recording");
+        let temporary = self.path.with_file_name(temporary);
+        std::fs::write(&temporary, contents)
+            .and_then(|_| std::fs::rename(&temporary, &self.path))
+            .map_err(|e| Error::Custom(format!("Failed to write cassette: {}", e)))?;
+
+        Ok(response)
+    }
+
+    fn replay_request(&self, request: &HttpRequest) -> Result<HttpResponse, Error> {
+        let (method, path, params) = key(request);
+        let mut played = self
+            .played
+            .lock()
+            .map_err(|_| "Cassette lock is poisoned")?;
+        let tape = self.tape.lock().map_err(|_| "Cassette lock is poisoned")?;
+
+        let matching: Vec<usize> = tape
+            .interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| i.matches(&method, &path, &params))
+            .map(|(index, _)| index)
+            .collect();
+        let index = matching
+            .iter()
+            .find(|&&index| !played[index])
+            .or_else(|| matching.last())
+            .copied()
+            .ok_or_else(|| {
+                Error::Custom(format!("No recorded response for {} {}", method, path))
+            })?;
+        played[index] = true;
+
+        let interaction = &tape.interactions[index];
+        let mut headers = HeaderMap::new();
+        for (name, value) in &interaction.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::try_from(name.as_str()),
+                HeaderValue::try_from(value.as_str()),
+            ) {
+                headers.append(name, value);
+            }
+        }
+        Ok(HttpResponse {
+            status: StatusCode::from_u16(interaction.status)
+                .map_err(|_| Error::Custom(format!("Invalid status: {}", interaction.status)))?,
+            headers,
+            body: interaction.body.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl HttpTransport for Cassette {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, Error> {
+        match &self.transport {
+            Some(transport) => self.record_request(transport.as_ref(), request).await,
+            None => self.replay_request(&request),
+        }
+    }
+}
+
+/// The method, the path without the host, and the sorted query and form parameters with secrets redacted.
+fn key(request: &HttpRequest) -> (String, String, Vec<(String, String)>) {
+    let url = request.url.as_str();
+    let url = url.find("://").map_or(url, |i| &url[i + 3..]);
+    let url = url.find('/').map_or("", |i| &url[i..]);
+    let (path, query) = match url.find('?') {
+        Some(i) => (&url[..i], &url[i + 1..]),
+        None => (url, ""),
+    };
+
+    let mut params: Vec<(String, String)> = serde_urlencoded::from_str(query).unwrap_or_default();
+    params.extend(
+        serde_urlencoded::from_str::<Vec<(String, String)>>(&request.body).unwrap_or_default(),
+    );
+    for (name, value) in params.iter_mut() {
+        if SECRET_PARAMS.contains(&name.as_str()) {
+            *value = REDACTED.to_string();
+        }
+    }
+    params.sort();
+
+    let path = if path.is_empty() { "/" } else { path };
+    (request.method.to_string(), path.to_string(), params)
+}
+
+/// Replaces the tokens in a token response, leaving every other body as it was sent.
+fn redact_body(body: &str) -> String {
+    let mut object = match serde_json::from_str::<Value>(body) {
+        Ok(Value::Object(object)) => object,
+        _ => return body.to_string(),
+    };
+    let mut redacted = false;
+    for field in SECRET_FIELDS {
+        if let Some(value) = object.get_mut(*field) {
+            *value = Value::String(REDACTED.to_string());
+            redacted = true;
+        }
+    }
+    if redacted {
+        Value::Object(object).to_string()
+    } else {
+        body.to_string()
+    }
+}
@@ -0,0 +1,62 @@
+//! The hosts the client sends its requests to.
+
+/// The base URLs of the reddit API, which can be pointed at a local server for testing.
+#[derive(Debug, Clone)]
+pub struct Endpoints {
+    oauth: String,
+    www: String,
+    token: Option<String>,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Endpoints {
+            oauth: "https://oauth.reddit.com".to_string(),
+            www: "https://www.reddit.com".to_string(),
+            token: None,
+        }
+    }
+}
+
+impl Endpoints {
+    /// Creates a new Endpoints struct pointing at reddit.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The host receiving the API requests, `https://oauth.reddit.com` by default.
+    pub fn oauth(mut self, url: &str) -> Self {
+        self.oauth = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// The host handling authorization and revoking tokens, `https://www.reddit.com` by default.
+    pub fn www(mut self, url: &str) -> Self {
+        self.www = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// The full URL of the token endpoint, `/api/v1/access_token` on the www host by default.
+    pub fn token(mut self, url: &str) -> Self {
+        self.token = Some(url.to_string());
+        self
+    }
+
+    pub(crate) fn api_url(&self, path: &str) -> String {
+        format!("{}{}", self.oauth, path)
+    }
+
+    pub(crate) fn access_token_url(&self) -> String {
+        self.token
+            .clone()
+            .unwrap_or_else(|| format!("{}/api/v1/access_token", self.www))
+    }
+
+    pub(crate) fn revoke_token_url(&self) -> String {
+        format!("{}/api/v1/revoke_token", self.www)
+    }
+
+    pub(crate) fn authorize_url(&self) -> String {
+        format!("{}/api/v1/authorize", self.www)
+    }
+}
@@ -0,0 +1,144 @@
+//! Keeps the client within the rate limit reddit reports in its response headers.
+
+use crate::error::Error;
+use futures_timer::Delay;
+use http::HeaderMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The request budget reported by reddit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// How many requests were used in the current window.
+    pub used: u64,
+    /// How many requests are left in the current window, minus the ones already scheduled by this client.
+    pub remaining: f64,
+    /// How long until the window resets.
+    pub reset: Duration,
+}
+
+/// How far apart requests are sent once a window is used up, until reddit reports the next budget.
+/// Reddit usually grants 600 requests per 10 minutes, so one a second.
+const DEFAULT_SPACING: Duration = Duration::from_secs(1);
+
+/// The longest reset accepted from the headers, far beyond reddit's 10 minute windows.
+const MAX_RESET: Duration = Duration::from_secs(3600);
+
+#[derive(Debug)]
+struct Window {
+    used: u64,
+    remaining: f64,
+    reset_at: Instant,
+    next_slot: Instant,
+    spacing: Duration,
+}
+
+/// A limiter spreading the remaining requests evenly over the rest of the window.
+/// Concurrent requests each reserve their own slot, so they do not burst through the budget together.
+/// Once the budget is used up, the waiting requests are sent one at a time after the reset.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    window: Mutex<Option<Window>>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter that knows no budget yet, letting requests through until the first update.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Waits until the next request may be sent.
+    pub async fn acquire(&self) -> Result<(), Error> {
+        let wait = self.reserve(Instant::now())?;
+        if wait > Duration::from_millis(0) {
+            Delay::new(wait).await;
+        }
+        Ok(())
+    }
+
+    /// Reserves the next slot for a request made at `now`, returning how long it has to wait for it.
+    pub fn reserve(&self, now: Instant) -> Result<Duration, Error> {
+        let mut window = self
+            .window
+            .lock()
+            .map_err(|_| "Rate limit lock is poisoned")?;
+        // Nothing is known before the first response.
+        let w = match window.as_mut() {
+            Some(w) => w,
+            None => return Ok(Duration::from_millis(0)),
+        };
+
+        let slot = if w.reset_at <= now {
+            // The window reset, the next budget is only known after a response reports it.
+            let slot = w.next_slot.max(now);
+            w.next_slot = slot + w.spacing;
+            slot
+        } else if w.remaining < 1.0 {
+            // Used up, the waiting requests are sent one after another once the window resets.
+            let slot = w.next_slot.max(w.reset_at);
+            w.next_slot = slot + w.spacing;
+            slot
+        } else {
+            let slot = w.next_slot.max(now);
+            let interval = w
+                .reset_at
+                .saturating_duration_since(slot)
+                .div_f64(w.remaining);
+            w.next_slot = slot + interval;
+            w.remaining = (w.remaining - 1.0).max(0.0);
+            slot
+        };
+        Ok(slot.saturating_duration_since(now))
+    }
+
+    /// Updates the budget from the response headers, which are missing on some responses such as error pages.
+    /// Headers that are not finite, non-negative numbers are treated as missing.
+    pub fn update(&self, headers: &HeaderMap) -> Result<(), Error> {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .filter(|v| v.is_finite() && *v >= 0.0)
+        };
+        let (used, remaining, reset) = match (
+            header("x-ratelimit-used"),
+            header("x-ratelimit-remaining"),
+            header("x-ratelimit-reset"),
+        ) {
+            (Some(used), Some(remaining), Some(reset)) => (used, remaining, reset),
+            _ => return Ok(()),
+        };
+
+        let now = Instant::now();
+        let mut window = self
+            .window
+            .lock()
+            .map_err(|_| "Rate limit lock is poisoned")?;
+        let reset = Duration::from_secs_f64(reset.min(MAX_RESET.as_secs_f64()));
+        let next_slot = window.as_ref().map_or(now, |w| w.next_slot);
+        let spacing = if remaining >= 1.0 {
+            reset.div_f64(remaining)
+        } else {
+            window.as_ref().map_or(DEFAULT_SPACING, |w| w.spacing)
+        };
+        *window = Some(Window {
+            used: used as u64,
+            remaining,
+            reset_at: now + reset,
+            next_slot,
+            spacing,
+        });
+        Ok(())
+    }
+
+    /// Returns the current budget, if reddit reported one.
+    pub fn current(&self) -> Option<RateLimit> {
+        let window = self.window.lock().ok()?;
+        window.as_ref().map(|w| RateLimit {
+            used: w.used,
+            remaining: w.remaining,
+            reset: w.reset_at.saturating_duration_since(Instant::now()),
+        })
+    }
+}
@@ -0,0 +1,143 @@
+//! Retrying requests that failed for reasons that are likely to go away.
+
+use crate::error::Error;
+use http::header::{HeaderMap, RETRY_AFTER};
+use http::{Method, StatusCode};
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Decides which failed requests are sent again, and how long to wait before that.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    statuses: Vec<u16>,
+    errors: Arc<dyn Fn(&Error) -> bool + Send + Sync>,
+    retry_post: bool,
+}
+
+/// Retries the requests that never got a response, such as timeouts and failed connections.
+fn transport_errors(error: &Error) -> bool {
+    matches!(error, Error::Transport(_))
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("base_delay", &self.base_delay)
+            .field("max_delay", &self.max_delay)
+            .field("statuses", &self.statuses)
+            .field("retry_post", &self.retry_post)
+            .finish()
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            statuses: vec![429, 500, 502, 503, 504],
+            errors: Arc::new(transport_errors),
+            retry_post: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a new RetryPolicy struct with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A policy sending every request only once.
+    pub fn never() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// How many times a request is sent at most, including the first attempt. 3 by default.
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// The delay before the first retry, doubled for every further one. Half a second by default.
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// The longest delay between two attempts, 30 seconds by default.
+    /// Responses asking to retry later than this with a `Retry-After` header are returned instead of retried.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// The status codes that are retried, 429, 500, 502, 503 and 504 by default.
+    pub fn retry_statuses(mut self, statuses: &[u16]) -> Self {
+        self.statuses = statuses.to_vec();
+        self
+    }
+
+    /// Which errors are retried, besides the statuses. By default the ones where reddit was never reached,
+    /// such as timeouts and failed connections.
+    pub fn retry_errors<F>(mut self, retries: F) -> Self
+    where
+        F: Fn(&Error) -> bool + Send + Sync + 'static,
+    {
+        self.errors = Arc::new(retries);
+        self
+    }
+
+    /// Also retry POST requests, which may perform the same action twice if reddit handled the first attempt.
+    /// Disabled by default.
+    pub fn retry_post(mut self, retry: bool) -> Self {
+        self.retry_post = retry;
+        self
+    }
+
+    pub(crate) fn allows(&self, method: &Method, attempt: u32) -> bool {
+        attempt < self.max_attempts && (*method != Method::POST || self.retry_post)
+    }
+
+    pub(crate) fn retries_status(&self, status: StatusCode) -> bool {
+        self.statuses.contains(&status.as_u16())
+    }
+
+    pub(crate) fn retries_error(&self, error: &Error) -> bool {
+        (self.errors)(error)
+    }
+
+    /// The delay after the given attempt, using the `Retry-After` header if reddit sent one.
+    /// Returns `None` if reddit asks to wait longer than the maximum delay, so the request is not retried.
+    pub(crate) fn delay(&self, attempt: u32, headers: Option<&HeaderMap>) -> Option<Duration> {
+        let retry_after = headers
+            .and_then(|h| h.get(RETRY_AFTER))
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok());
+        if let Some(seconds) = retry_after {
+            let delay = Duration::from_secs(seconds);
+            return if delay <= self.max_delay {
+                Some(delay)
+            } else {
+                None
+            };
+        }
+
+        let backoff = self
+            .base_delay
+            .checked_mul(1 << (attempt - 1).min(16))
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        // Half of the delay is random, so clients failing together do not retry together.
+        let jitter = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+        Some(backoff / 2 + (backoff / 2).mul_f64(jitter))
+    }
+}
@@ -1,14 +1,22 @@
+use crate::auth::scope::Scope;
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Route {
     UserAbout(String),
+    UserSubmitted(String),
+    UserComments(String),
     SubredditAbout(String),
     SubredditHot(String),
     SubredditNew(String),
     SubredditRising(String),
+    SubredditTop(String),
+    SubredditControversial(String),
+    SubredditComments(String),
+    #[allow(dead_code)]
     SubredditArticle(String, String),
     Info,
+    MoreChildren,
     Comment,
     Submission(String),
     SubmissionComment(String, String),
@@ -22,24 +30,86 @@ pub enum Route {
     Unlock,
     Follow,
     Submit,
+    #[allow(dead_code)]
     Me,
     Friends(String),
     ReportUser,
     BlockUser,
     UsernameAvailable,
+    MessageInbox,
+    MessageUnread,
+    MessageSent,
+    MessageMentions,
+    MessageCommentReplies,
+    Compose,
+    ReadMessage,
+    UnreadMessage,
+    ReadAllMessages,
+    DelMsg,
+    Block,
+    EditUserText,
+    Del,
+}
+
+impl Route {
+    /// The scopes allowing a token to use this route, any one of them is enough.
+    /// Empty if the route needs no particular scope.
+    pub(crate) fn scopes(&self) -> &'static [Scope] {
+        match self {
+            Route::UserAbout(_)
+            | Route::SubredditAbout(_)
+            | Route::SubredditHot(_)
+            | Route::SubredditNew(_)
+            | Route::SubredditRising(_)
+            | Route::SubredditTop(_)
+            | Route::SubredditControversial(_)
+            | Route::SubredditComments(_)
+            | Route::SubredditArticle(_, _)
+            | Route::Info
+            | Route::MoreChildren
+            | Route::Submission(_)
+            | Route::SubmissionComment(_, _) => &[Scope::Read],
+            Route::Comment => &[Scope::Submit, Scope::PrivateMessages],
+            Route::Submit => &[Scope::Submit],
+            Route::Spoiler | Route::Unspoiler | Route::EditUserText | Route::Del => &[Scope::Edit],
+            Route::SetNSFW | Route::UnsetNSFW | Route::Lock | Route::Unlock => &[Scope::ModPosts],
+            Route::Follow | Route::Friends(_) => &[Scope::Subscribe],
+            Route::ReportAward | Route::ReportUser => &[Scope::Report],
+            Route::BlockUser => &[Scope::Account],
+            Route::Me => &[Scope::Identity],
+            Route::UserSubmitted(_) | Route::UserComments(_) => &[Scope::History],
+            Route::MessageInbox
+            | Route::MessageUnread
+            | Route::MessageSent
+            | Route::MessageMentions
+            | Route::MessageCommentReplies
+            | Route::Compose
+            | Route::ReadMessage
+            | Route::UnreadMessage
+            | Route::ReadAllMessages
+            | Route::DelMsg
+            | Route::Block => &[Scope::PrivateMessages],
+            Route::Custom(_) | Route::UsernameAvailable => &[],
+        }
+    }
 }
 
 impl fmt::Display for Route {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let base = "https://oauth.reddit.com";
         let route = match self {
             Route::UserAbout(ua) => format!("/user/{}/about", ua),
+            Route::UserSubmitted(us) => format!("/user/{}/submitted", us),
+            Route::UserComments(uc) => format!("/user/{}/comments", uc),
             Route::SubredditAbout(sa) => format!("/r/{}/about", sa),
             Route::SubredditHot(sh) => format!("/r/{}/hot", sh),
             Route::SubredditNew(sn) => format!("/r/{}/new", sn),
             Route::SubredditRising(sr) => format!("/r/{}/rising", sr),
+            Route::SubredditTop(st) => format!("/r/{}/top", st),
+            Route::SubredditControversial(sc) => format!("/r/{}/controversial", sc),
+            Route::SubredditComments(sc) => format!("/r/{}/comments", sc),
             Route::SubredditArticle(s, a) => format!("/r/{}/comments/{}", s, a),
             Route::Info => "/api/info".into(),
+            Route::MoreChildren => "/api/morechildren".into(),
             Route::Comment => "/api/comment".into(),
             Route::Submission(s) => format!("/comments/{}/", s),
             Route::SubmissionComment(s, c) => format!("/comments/{}/_/{}", s, c),
@@ -58,7 +128,20 @@ impl fmt::Display for Route {
             Route::ReportUser => "/api/report_user".into(),
             Route::BlockUser => "/api/block_user".into(),
             Route::UsernameAvailable => "/api/username_available".into(),
+            Route::MessageInbox => "/message/inbox".into(),
+            Route::MessageUnread => "/message/unread".into(),
+            Route::MessageSent => "/message/sent".into(),
+            Route::MessageMentions => "/message/mentions".into(),
+            Route::MessageCommentReplies => "/message/comments".into(),
+            Route::Compose => "/api/compose".into(),
+            Route::ReadMessage => "/api/read_message".into(),
+            Route::UnreadMessage => "/api/unread_message".into(),
+            Route::ReadAllMessages => "/api/read_all_messages".into(),
+            Route::DelMsg => "/api/del_msg".into(),
+            Route::Block => "/api/block".into(),
+            Route::EditUserText => "/api/editusertext".into(),
+            Route::Del => "/api/del".into(),
         };
-        write!(f, "{}{}", base, route)
+        f.write_str(&route)
     }
 }
@@ -0,0 +1,153 @@
+//! The HTTP layer the client sends its requests through.
+
+use crate::error::Error;
+use async_trait::async_trait;
+use http::header::{HeaderValue, USER_AGENT};
+use http::{HeaderMap, Method, StatusCode};
+use reqwest::Client as HttpClient;
+use serde::Serialize;
+use std::convert::TryFrom;
+use std::fmt::Debug;
+
+/// A request to be sent by a transport.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    /// The HTTP method.
+    pub method: Method,
+    /// The full URL, including the query string.
+    pub url: String,
+    /// The headers, such as the authorization and the user agent.
+    pub headers: HeaderMap,
+    /// The URL encoded form body, empty for requests without one.
+    pub body: String,
+}
+
+impl HttpRequest {
+    /// Creates a request without headers or a body.
+    pub fn new(method: Method, url: &str) -> Self {
+        HttpRequest {
+            method,
+            url: url.to_string(),
+            headers: HeaderMap::new(),
+            body: String::new(),
+        }
+    }
+
+    /// Adds a header, failing if it is not a valid header.
+    pub fn header(mut self, name: &str, value: &str) -> Result<Self, Error> {
+        let name = http::header::HeaderName::try_from(name)
+            .map_err(|_| Error::Custom(format!("Invalid header name: {}", name)))?;
+        let value = http::header::HeaderValue::try_from(value)
+            .map_err(|_| Error::Custom(format!("Invalid header value: {}", value)))?;
+        self.headers.insert(name, value);
+        Ok(self)
+    }
+
+    /// Sets a URL encoded form as the body.
+    pub fn form<F: Serialize + ?Sized>(mut self, form: &F) -> Result<Self, Error> {
+        self.body = serde_urlencoded::to_string(form)
+            .map_err(|e| Error::Custom(format!("Invalid form: {}", e)))?;
+        self.header("Content-Type", "application/x-www-form-urlencoded")
+    }
+
+    /// Adds HTTP basic authentication.
+    pub fn basic_auth(self, username: &str, password: &str) -> Result<Self, Error> {
+        let credentials = base64::encode(format!("{}:{}", username, password));
+        self.header("Authorization", &format!("Basic {}", credentials))
+    }
+
+    /// Adds bearer token authentication.
+    pub fn bearer_auth(self, token: &str) -> Result<Self, Error> {
+        self.header("Authorization", &format!("Bearer {}", token))
+    }
+}
+
+/// A response returned by a transport.
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    /// The HTTP status code.
+    pub status: StatusCode,
+    /// The response headers, including the rate limit headers.
+    pub headers: HeaderMap,
+    /// The response body.
+    pub body: String,
+}
+
+/// Sends HTTP requests, the default implementation being `ReqwestTransport`.
+/// A custom transport can stand in for reddit in tests, or use another HTTP library.
+#[async_trait]
+pub trait HttpTransport: Debug + Send + Sync {
+    /// Sends the request and reads the whole response.
+    /// Failures to reach the server should be returned as `Error::Transport`, which the client retries.
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, Error>;
+}
+
+/// Adds the user agent to every request sent through the inner transport, including the ones logging in.
+#[derive(Debug)]
+pub(crate) struct UserAgentTransport {
+    inner: Box<dyn HttpTransport>,
+    user_agent: HeaderValue,
+}
+
+impl UserAgentTransport {
+    pub(crate) fn new(inner: Box<dyn HttpTransport>, user_agent: &str) -> Result<Self, Error> {
+        let user_agent = HeaderValue::from_str(user_agent)
+            .map_err(|_| Error::Custom(format!("Invalid user agent: {}", user_agent)))?;
+        Ok(UserAgentTransport { inner, user_agent })
+    }
+}
+
+#[async_trait]
+impl HttpTransport for UserAgentTransport {
+    async fn send(&self, mut request: HttpRequest) -> Result<HttpResponse, Error> {
+        request.headers.insert(USER_AGENT, self.user_agent.clone());
+        self.inner.send(request).await
+    }
+}
+
+/// The default transport, using reqwest.
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: HttpClient,
+}
+
+impl ReqwestTransport {
+    /// Creates a transport using a default reqwest client.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a transport using an already configured reqwest client.
+    pub fn with_client(client: HttpClient) -> Self {
+        ReqwestTransport { client }
+    }
+}
+
+#[async_trait]
+impl HttpTransport for ReqwestTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, Error> {
+        let response = self
+            .client
+            .request(request.method, &request.url)
+            .headers(request.headers)
+            .body(request.body)
+            .send()
+            .await
+            .map_err(transport_error)?;
+
+        Ok(HttpResponse {
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: response.text().await.map_err(transport_error)?,
+        })
+    }
+}
+
+/// Failures to reach reddit become `Error::Transport`, requests reqwest could not even build stay as they are.
+fn transport_error(error: reqwest::Error) -> Error {
+    if error.is_builder() {
+        Error::Reqwest(error)
+    } else {
+        Error::Transport(error.to_string())
+    }
+}
@@ -1,30 +1,44 @@
 //! The error type for this library.
 
+use crate::auth::scope::Scope;
 use http::header::ToStrError;
 use reqwest::Error as ReqwestError;
 use serde_json::Error as SerdeError;
 use std::error::Error as StdError;
 use std::num::ParseFloatError;
 use std::num::ParseIntError;
+use std::time::Duration;
 use std::{fmt, fmt::Display};
 
 /// The generic error type used for handling errors within this library.
 #[derive(Debug)]
 pub enum Error {
+    /// Reddit refused the request, explaining why.
+    Api(ApiError),
     /// Any other error which couldn't be represented well otherwise.
     Custom(String),
+    /// The client was logged out, so it cannot send any more requests.
+    LoggedOut,
     /// A header is missing.
     MissingHeader(String),
+    /// The access token was not granted the scope needed for the request.
+    MissingScope(Scope),
     /// An error occured during parsing a float.
     ParseFloatError(ParseFloatError),
     /// An error occured during parsing an integer.
     ParseIntError(ParseIntError),
+    /// The authentication method only allows reading, such as `ApplicationAuth`.
+    ReadOnly,
     /// Reqwest errors.
     Reqwest(ReqwestError),
     /// Serde errors.
     Serde(SerdeError),
+    /// Reddit responded with an unsuccessful HTTP status code, without explaining why.
+    Status(u16),
     /// I wish there was a better way to do this.
     ToStrError(ToStrError),
+    /// The transport failed to reach the server or to read its response.
+    Transport(String),
 }
 
 impl StdError for Error {}
@@ -32,18 +46,34 @@ impl StdError for Error {}
 impl Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let reason = match self {
+            Error::Api(api_e) => format!("API Error: {}", api_e),
             Error::Custom(custom_e) => format!("Error: {:?}", custom_e),
+            Error::LoggedOut => {
+                "Logged Out: the tokens were revoked, a new client has to log in again".to_string()
+            }
             Error::MissingHeader(header_e) => format!("Missing Header: {:?}", header_e),
+            Error::MissingScope(scope) => format!("Missing Scope: {}", scope),
             Error::ParseFloatError(parsefloat_e) => format!("ParseFloat Error: {:?}", parsefloat_e),
             Error::ParseIntError(parseint_e) => format!("ParseInt Error: {:?}", parseint_e),
+            Error::ReadOnly => {
+                "Read Only: this authentication method cannot perform actions".to_string()
+            }
             Error::Reqwest(http_e) => format!("Reqwest Error: {:?}", http_e),
             Error::Serde(json_e) => format!("Serde Error: {:?}", json_e),
+            Error::Status(status) => format!("Status Error: reddit responded with {}", status),
             Error::ToStrError(tostr_e) => format!("ToStr Error: {:?}", tostr_e),
+            Error::Transport(transport_e) => format!("Transport Error: {}", transport_e),
         };
         f.write_str(&reason)
     }
 }
 
+impl From<ApiError> for Error {
+    fn from(e: ApiError) -> Error {
+        Error::Api(e)
+    }
+}
+
 impl From<ParseIntError> for Error {
     fn from(e: ParseIntError) -> Error {
         Error::ParseIntError(e)
@@ -85,3 +115,87 @@ impl From<ToStrError> for Error {
         Error::ToStrError(e)
     }
 }
+
+/// An error reported by reddit in the `json.errors` field of a response.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiError {
+    /// Too many actions of this kind were performed, the duration is how long to wait before trying again.
+    RateLimit(Duration),
+    /// The thread is locked, so it cannot be replied to.
+    ThreadLocked,
+    /// The comment was deleted.
+    DeletedComment,
+    /// The subreddit does not exist.
+    SubredditNoExist,
+    /// A text is required.
+    NoText,
+    /// A text is longer than allowed.
+    TooLong,
+    /// The action requires a logged in user.
+    UserRequired,
+    /// Any other error, with its code, message and the field it relates to.
+    Other(String, String, Option<String>),
+}
+
+impl ApiError {
+    /// Builds the error from the code, message and field reddit sent.
+    pub fn new(code: &str, message: &str, field: Option<&str>) -> Self {
+        match code {
+            "RATELIMIT" => ApiError::RateLimit(parse_wait(message)),
+            "THREAD_LOCKED" => ApiError::ThreadLocked,
+            "DELETED_COMMENT" => ApiError::DeletedComment,
+            "SUBREDDIT_NOEXIST" => ApiError::SubredditNoExist,
+            "NO_TEXT" => ApiError::NoText,
+            "TOO_LONG" => ApiError::TooLong,
+            "USER_REQUIRED" => ApiError::UserRequired,
+            _ => ApiError::Other(
+                code.to_string(),
+                message.to_string(),
+                field.map(|f| f.to_string()),
+            ),
+        }
+    }
+
+    /// Returns the first error of an `api_type=json` response, if there is one.
+    pub(crate) fn from_json(value: &serde_json::Value) -> Option<Self> {
+        let error = value.get("json")?.get("errors")?.as_array()?.first()?;
+        let parts = error.as_array()?;
+        let part = |i: usize| parts.get(i).and_then(|p| p.as_str());
+        Some(ApiError::new(part(0)?, part(1).unwrap_or(""), part(2)))
+    }
+}
+
+/// Finds the duration in messages like "Take a break for 5 minutes before trying again."
+fn parse_wait(message: &str) -> Duration {
+    let words: Vec<&str> = message.split_whitespace().collect();
+    for pair in words.windows(2) {
+        if let Ok(amount) = pair[0].parse::<u64>() {
+            let unit = pair[1].to_lowercase();
+            if unit.starts_with("millisecond") {
+                return Duration::from_millis(amount);
+            } else if unit.starts_with("second") {
+                return Duration::from_secs(amount);
+            } else if unit.starts_with("minute") {
+                return Duration::from_secs(amount.saturating_mul(60));
+            } else if unit.starts_with("hour") {
+                return Duration::from_secs(amount.saturating_mul(3600));
+            }
+        }
+    }
+    Duration::from_secs(0)
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::RateLimit(wait) => write!(f, "rate limited for {} seconds", wait.as_secs()),
+            ApiError::ThreadLocked => f.write_str("the thread is locked"),
+            ApiError::DeletedComment => f.write_str("the comment was deleted"),
+            ApiError::SubredditNoExist => f.write_str("the subreddit does not exist"),
+            ApiError::NoText => f.write_str("a text is required"),
+            ApiError::TooLong => f.write_str("the text is too long"),
+            ApiError::UserRequired => f.write_str("a logged in user is required"),
+            ApiError::Other(code, message, _) => write!(f, "{}: {}", code, message),
+        }
+    }
+}
@@ -23,3 +23,4 @@ pub mod client;
 pub mod error;
 pub mod model;
 pub mod prelude;
+pub mod stream;
@@ -2,12 +2,12 @@
 
 pub mod award;
 pub mod comment;
+pub mod commenttree;
 pub mod link;
 #[doc(hidden)]
 pub mod listing;
 pub mod message;
 pub mod misc;
-#[doc(hidden)]
 pub mod more;
 pub mod prelude;
 pub mod subreddit;
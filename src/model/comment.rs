@@ -26,6 +26,9 @@ pub struct Comment {
     pub body: String,
     /// The body of the comment as HTML.
     pub body_html: String,
+    /// The path to this comment, relative to the reddit host.
+    #[serde(default)]
+    pub permalink: String,
     #[doc(hidden)]
     pub replies: Option<Box<Thing>>,
 }
@@ -71,8 +74,28 @@ impl Comment {
         &self,
         client: &Client<T>,
         body: &str,
-    ) -> Result<(), Error> {
-        client.submit_comment(self.name.as_ref(), body).await
+    ) -> Result<Comment, Error> {
+        client
+            .submit_comment(self.name.as_ref(), body)
+            .await?
+            .try_into()
+    }
+
+    /// Replaces the body of this comment, returning the edited comment.
+    pub async fn edit<T: Auth + Send + Sync>(
+        &self,
+        client: &Client<T>,
+        body: &str,
+    ) -> Result<Comment, Error> {
+        client.edit_text(self.name.as_ref(), body).await?.try_into()
+    }
+
+    /// Deletes this comment.
+    pub async fn delete<T: Auth + Send + Sync>(&self, client: &Client<T>) -> Result<(), Error> {
+        client
+            .post(Route::Del, &Params::new().add("id", self.name.as_ref()))
+            .await?;
+        Ok(())
     }
 
     /// Locks the Comment assuming you have the permission to do so.
@@ -0,0 +1,223 @@
+//! Contains the comment tree model.
+
+use crate::{
+    error::Error,
+    model::{
+        comment::Comment,
+        listing::Listing,
+        misc::Fullname,
+        more::{flatten, More},
+        thing::Thing,
+    },
+};
+use std::collections::{HashMap, VecDeque};
+use std::convert::{TryFrom, TryInto};
+
+/// A single comment inside a CommentTree.
+#[derive(Debug, Clone)]
+pub struct CommentNode {
+    /// The comment itself. Its replies are stored in the tree instead.
+    pub comment: Comment,
+    /// How deep the comment is in the tree, the roots have a depth of 0.
+    pub depth: usize,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+/// An owned tree of comments, usually the comment section of a Link.
+#[derive(Debug, Clone, Default)]
+pub struct CommentTree {
+    nodes: Vec<CommentNode>,
+    roots: Vec<usize>,
+    index: HashMap<Fullname, usize>,
+    more: Vec<More>,
+}
+
+impl CommentTree {
+    fn from_things(things: Vec<Thing>) -> Self {
+        let mut comments: Vec<Comment> = Vec::new();
+        let mut more: VecDeque<More> = VecDeque::new();
+        flatten(things, &mut comments, &mut more);
+
+        let mut tree = CommentTree {
+            more: more.into_iter().collect(),
+            ..CommentTree::default()
+        };
+        // Parents always come before their replies, so they are already indexed.
+        for comment in comments {
+            if tree.index.contains_key(&comment.name) {
+                continue;
+            }
+            let id = tree.nodes.len();
+            let parent = tree.index.get(&comment.parent_id).copied();
+            let depth = match parent {
+                Some(p) => {
+                    tree.nodes[p].children.push(id);
+                    tree.nodes[p].depth + 1
+                }
+                None => {
+                    tree.roots.push(id);
+                    0
+                }
+            };
+            tree.index.insert(comment.name.clone(), id);
+            tree.nodes.push(CommentNode {
+                comment,
+                depth,
+                parent,
+                children: Vec::new(),
+            });
+        }
+        tree
+    }
+
+    /// The amount of comments in the tree.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether the tree has no comments.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the top-level comments of the tree.
+    pub fn roots(&self) -> impl Iterator<Item = &CommentNode> {
+        self.roots.iter().map(move |&id| &self.nodes[id])
+    }
+
+    /// Returns the comment with the given Fullname.
+    pub fn get(&self, name: &Fullname) -> Option<&CommentNode> {
+        self.index.get(name).map(|&id| &self.nodes[id])
+    }
+
+    /// Returns the direct replies to the comment with the given Fullname.
+    pub fn children(&self, name: &Fullname) -> impl Iterator<Item = &CommentNode> {
+        let children = match self.index.get(name) {
+            Some(&id) => self.nodes[id].children.as_slice(),
+            None => &[],
+        };
+        children.iter().map(move |&id| &self.nodes[id])
+    }
+
+    /// Returns the parent of the comment with the given Fullname, if it is part of the tree.
+    pub fn parent(&self, name: &Fullname) -> Option<&CommentNode> {
+        let id = *self.index.get(name)?;
+        self.nodes[id].parent.map(|p| &self.nodes[p])
+    }
+
+    /// Returns the ancestors of the comment with the given Fullname, starting with its parent.
+    pub fn ancestors(&self, name: &Fullname) -> Ancestors<'_> {
+        let next = self.index.get(name).and_then(|&id| self.nodes[id].parent);
+        Ancestors { tree: self, next }
+    }
+
+    /// Iterates over the comments depth-first, in the order they are displayed on reddit.
+    pub fn depth_first(&self) -> DepthFirst<'_> {
+        DepthFirst {
+            tree: self,
+            stack: self.roots.iter().rev().copied().collect(),
+        }
+    }
+
+    /// Iterates over the comments breadth-first, one depth level at a time.
+    pub fn breadth_first(&self) -> BreadthFirst<'_> {
+        BreadthFirst {
+            tree: self,
+            queue: self.roots.iter().copied().collect(),
+        }
+    }
+
+    /// Returns the placeholders for comments that were not loaded yet.
+    pub fn more(&self) -> &[More] {
+        &self.more
+    }
+
+    /// Consumes the tree, returning all comments in depth-first order.
+    pub fn flatten(self) -> Vec<Comment> {
+        let mut order: Vec<usize> = Vec::with_capacity(self.nodes.len());
+        let mut stack: Vec<usize> = self.roots.iter().rev().copied().collect();
+        while let Some(id) = stack.pop() {
+            order.push(id);
+            stack.extend(self.nodes[id].children.iter().rev());
+        }
+        let mut nodes: Vec<Option<CommentNode>> = self.nodes.into_iter().map(Some).collect();
+        order
+            .into_iter()
+            .filter_map(|id| nodes[id].take())
+            .map(|node| node.comment)
+            .collect()
+    }
+}
+
+impl From<Vec<Comment>> for CommentTree {
+    fn from(comments: Vec<Comment>) -> Self {
+        CommentTree::from_things(comments.into_iter().map(Thing::Comment).collect())
+    }
+}
+
+impl From<Listing> for CommentTree {
+    fn from(listing: Listing) -> Self {
+        CommentTree::from_things(listing.children)
+    }
+}
+
+/// Builds the tree from the response of the article endpoint, the link listing followed by the comment listing.
+impl TryFrom<Vec<Thing>> for CommentTree {
+    type Error = Error;
+    fn try_from(mut value: Vec<Thing>) -> Result<Self, Self::Error> {
+        if value.len() < 2 {
+            return Err(Error::Custom("Missing comment listing".into()));
+        }
+        let listing: Listing = value.remove(1).try_into()?;
+        Ok(CommentTree::from(listing))
+    }
+}
+
+/// Iterator over the ancestors of a comment, see `CommentTree::ancestors`.
+#[derive(Debug)]
+pub struct Ancestors<'a> {
+    tree: &'a CommentTree,
+    next: Option<usize>,
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = &'a CommentNode;
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = &self.tree.nodes[self.next?];
+        self.next = node.parent;
+        Some(node)
+    }
+}
+
+/// Depth-first iterator over a CommentTree, see `CommentTree::depth_first`.
+#[derive(Debug)]
+pub struct DepthFirst<'a> {
+    tree: &'a CommentTree,
+    stack: Vec<usize>,
+}
+
+impl<'a> Iterator for DepthFirst<'a> {
+    type Item = &'a CommentNode;
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = &self.tree.nodes[self.stack.pop()?];
+        self.stack.extend(node.children.iter().rev());
+        Some(node)
+    }
+}
+
+/// Breadth-first iterator over a CommentTree, see `CommentTree::breadth_first`.
+#[derive(Debug)]
+pub struct BreadthFirst<'a> {
+    tree: &'a CommentTree,
+    queue: VecDeque<usize>,
+}
+
+impl<'a> Iterator for BreadthFirst<'a> {
+    type Item = &'a CommentNode;
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = &self.tree.nodes[self.queue.pop_front()?];
+        self.queue.extend(node.children.iter());
+        Some(node)
+    }
+}
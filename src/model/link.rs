@@ -5,11 +5,17 @@ use crate::{
     client::{route::Route, Client},
     error::Error,
     model::{
-        award::Award, comment::Comment, misc::Fullname, misc::Params, subreddit::Subreddit,
+        award::Award,
+        comment::Comment,
+        commenttree::CommentTree,
+        misc::{CommentOptions, Fullname, Params},
+        subreddit::Subreddit,
+        thing::Thing,
         user::User,
     },
 };
 use serde::{Deserialize, Serialize};
+use std::convert::TryInto;
 
 /// The struct representing a post on reddit.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -26,6 +32,12 @@ pub struct Link {
     pub subreddit: String,
     /// The fullname of this Link.
     pub name: Fullname,
+    /// The path to this Link, relative to the reddit host.
+    #[serde(default)]
+    pub permalink: String,
+    /// The URL the Link points to, or its permalink for text posts.
+    #[serde(default)]
+    pub url: String,
     /// All awards belonging to this Link.
     pub all_awardings: Vec<Award>,
 }
@@ -45,11 +57,43 @@ impl Link {
     }
 
     /// Returns the first-level replies to this post.
+    /// Placeholders for comments that were not loaded are skipped.
     pub async fn replies<T: Auth + Send + Sync>(
         &self,
-        _client: &Client<T>,
+        client: &Client<T>,
+        options: &CommentOptions,
+    ) -> Result<Vec<Comment>, Error> {
+        let things = client.link_comments(&self.name.name(), options).await?;
+        let mut comments: Vec<Comment> = Vec::new();
+        for thing in things {
+            if let Thing::Comment(comment) = thing {
+                comments.push(comment);
+            }
+        }
+        Ok(comments)
+    }
+
+    /// Returns the replies to this post with every placeholder expanded.
+    /// Large threads can take many requests to load completely.
+    pub async fn all_replies<T: Auth + Send + Sync>(
+        &self,
+        client: &Client<T>,
+        options: &CommentOptions,
     ) -> Result<Vec<Comment>, Error> {
-        todo!()
+        let things = client.link_comments(&self.name.name(), options).await?;
+        client
+            .load_thread(&self.name, things, &self.name, options)
+            .await
+    }
+
+    /// Returns the comment section of this post as a tree.
+    /// Placeholders are not expanded, they can be found using `CommentTree::more`.
+    pub async fn comment_tree<T: Auth + Send + Sync>(
+        &self,
+        client: &Client<T>,
+        options: &CommentOptions,
+    ) -> Result<CommentTree, Error> {
+        client.comment_tree(&self.name.name(), options).await
     }
 
     /// Creates a top-level comment in this Link.
@@ -57,8 +101,28 @@ impl Link {
         &self,
         client: &Client<T>,
         body: &str,
-    ) -> Result<(), Error> {
-        client.submit_comment(self.name.as_ref(), body).await
+    ) -> Result<Comment, Error> {
+        client
+            .submit_comment(self.name.as_ref(), body)
+            .await?
+            .try_into()
+    }
+
+    /// Replaces the text of this self post, returning the edited Link.
+    pub async fn edit<T: Auth + Send + Sync>(
+        &self,
+        client: &Client<T>,
+        text: &str,
+    ) -> Result<Link, Error> {
+        client.edit_text(self.name.as_ref(), text).await?.try_into()
+    }
+
+    /// Deletes this Link.
+    pub async fn delete<T: Auth + Send + Sync>(&self, client: &Client<T>) -> Result<(), Error> {
+        client
+            .post(Route::Del, &Params::new().add("id", self.name.as_ref()))
+            .await?;
+        Ok(())
     }
 
     /// Spoilers the Link assuming you have the permission to do so.
@@ -1,12 +1,12 @@
 //! Module containing the Listing struct.
 
 use crate::error::Error;
-use crate::model::{comment::Comment, link::Link, misc::Fullname, thing::Thing};
+use crate::model::{comment::Comment, link::Link, message::Message, misc::Fullname, thing::Thing};
 use serde::{Deserialize, Serialize};
 use std::convert::{TryFrom, TryInto};
 
 /// Listing is the general struct returned by most methods, containing the actual data and options to filter the data returned.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Listing {
     /// Legacy way to authenticate mod actions.
     pub modhash: Option<String>,
@@ -29,21 +29,6 @@ pub struct Listing {
     pub show: Option<String>,
 }
 
-impl Default for Listing {
-    fn default() -> Self {
-        Listing {
-            modhash: None,
-            dist: None,
-            children: Vec::new(),
-            after: None,
-            before: None,
-            limit: None,
-            count: None,
-            show: None,
-        }
-    }
-}
-
 impl TryFrom<Listing> for Vec<Comment> {
     type Error = Error;
     fn try_from(value: Listing) -> Result<Self, Self::Error> {
@@ -61,3 +46,12 @@ impl TryFrom<Listing> for Vec<Link> {
         comments
     }
 }
+
+impl TryFrom<Listing> for Vec<Message> {
+    type Error = Error;
+    fn try_from(value: Listing) -> Result<Self, Self::Error> {
+        let messages: Result<Vec<Message>, Error> =
+            value.children.into_iter().map(Thing::try_into).collect();
+        messages
+    }
+}
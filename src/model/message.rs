@@ -1,6 +1,179 @@
 //! Module for the message struct.
+
+use crate::{
+    auth::Auth,
+    client::{route::Route, Client},
+    error::Error,
+    model::{
+        comment::Comment,
+        misc::{Fullname, Params},
+        thing::Thing,
+    },
+};
 use serde::{Deserialize, Serialize};
 
 /// The struct representing a message.
+/// Besides private messages, comment replies and username mentions in the inbox are messages too.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Message {}
+pub struct Message {
+    /// The Fullname of this Message, or of the comment if it was one.
+    pub name: Fullname,
+    /// The username of the author, missing if the message was sent by a subreddit.
+    pub author: Option<String>,
+    /// The recipient of the message, either a username or a subreddit prefixed with `#`.
+    pub dest: String,
+    /// The subject of the message.
+    pub subject: String,
+    /// The body of the message.
+    pub body: String,
+    /// The body of the message as HTML.
+    pub body_html: String,
+    /// If this is a comment reply or a username mention instead of a private message.
+    pub was_comment: bool,
+    /// If the message is unread.
+    #[serde(default)]
+    pub new: bool,
+    /// The Fullname of the parent, either another message or the replied to thing.
+    pub parent_id: Option<Fullname>,
+    /// The Fullname of the first message in this conversation.
+    pub first_message_name: Option<Fullname>,
+    /// When the message was sent, normalized to UTC.
+    pub created_utc: f64,
+    /// The permalink to the comment, empty for private messages.
+    pub context: String,
+    /// The subreddit the comment or the modmail belongs to.
+    pub subreddit: Option<String>,
+    /// The title of the link the comment was made in.
+    pub link_title: Option<String>,
+    /// What kind of inbox item this is, such as `comment_reply` or `username_mention`.
+    #[serde(rename = "type", default)]
+    pub message_type: Option<String>,
+}
+
+/// An item in the inbox, sorted by what caused it.
+#[derive(Clone, Debug)]
+pub enum InboxItem {
+    /// A private message.
+    Message(Message),
+    /// A reply to a comment of the current user.
+    CommentReply(Message),
+    /// A comment mentioning the current user.
+    Mention(Message),
+    /// A reply to a post of the current user.
+    PostReply(Message),
+}
+
+impl InboxItem {
+    /// Returns the underlying message.
+    pub fn message(&self) -> &Message {
+        match self {
+            InboxItem::Message(m)
+            | InboxItem::CommentReply(m)
+            | InboxItem::Mention(m)
+            | InboxItem::PostReply(m) => m,
+        }
+    }
+
+    /// Consumes the item, returning the underlying message.
+    pub fn into_message(self) -> Message {
+        match self {
+            InboxItem::Message(m)
+            | InboxItem::CommentReply(m)
+            | InboxItem::Mention(m)
+            | InboxItem::PostReply(m) => m,
+        }
+    }
+}
+
+impl From<Message> for InboxItem {
+    fn from(message: Message) -> Self {
+        if !message.was_comment {
+            return InboxItem::Message(message);
+        }
+        let kind = message
+            .message_type
+            .clone()
+            .unwrap_or_else(|| message.subject.replace(' ', "_"));
+        match kind.as_str() {
+            "username_mention" => InboxItem::Mention(message),
+            "post_reply" => InboxItem::PostReply(message),
+            _ => InboxItem::CommentReply(message),
+        }
+    }
+}
+
+/// A reply sent to an item of the inbox.
+#[derive(Clone, Debug)]
+pub enum Reply {
+    /// A reply to a comment or mention, which is a comment itself.
+    Comment(Comment),
+    /// A reply to a private message.
+    Message(Message),
+}
+
+impl Reply {
+    /// Returns the fullname of the reply.
+    pub fn name(&self) -> &Fullname {
+        match self {
+            Reply::Comment(comment) => &comment.name,
+            Reply::Message(message) => &message.name,
+        }
+    }
+}
+
+impl Message {
+    /// Send a reply to this message, returning the created reply.
+    /// Replies to private messages are messages themselves, replies to comments are comments.
+    pub async fn reply<T: Auth + Send + Sync>(
+        &self,
+        client: &Client<T>,
+        body: &str,
+    ) -> Result<Reply, Error> {
+        match client.submit_comment(self.name.as_ref(), body).await? {
+            Thing::Comment(comment) => Ok(Reply::Comment(comment)),
+            Thing::Message(message) => Ok(Reply::Message(message)),
+            _ => Err(Error::Custom("Unexpected reply type".into())),
+        }
+    }
+
+    /// Marks the message as read.
+    pub async fn mark_read<T: Auth + Send + Sync>(&self, client: &Client<T>) -> Result<(), Error> {
+        client
+            .post(
+                Route::ReadMessage,
+                &Params::new().add("id", self.name.as_ref()),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Marks the message as unread.
+    pub async fn mark_unread<T: Auth + Send + Sync>(
+        &self,
+        client: &Client<T>,
+    ) -> Result<(), Error> {
+        client
+            .post(
+                Route::UnreadMessage,
+                &Params::new().add("id", self.name.as_ref()),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Deletes the message from the inbox.
+    pub async fn delete<T: Auth + Send + Sync>(&self, client: &Client<T>) -> Result<(), Error> {
+        client
+            .post(Route::DelMsg, &Params::new().add("id", self.name.as_ref()))
+            .await?;
+        Ok(())
+    }
+
+    /// Blocks the author of the message.
+    pub async fn block<T: Auth + Send + Sync>(&self, client: &Client<T>) -> Result<(), Error> {
+        client
+            .post(Route::Block, &Params::new().add("id", self.name.as_ref()))
+            .await?;
+        Ok(())
+    }
+}
@@ -25,8 +25,8 @@ pub struct QuarantinePermissions {
     pub crossposts: bool,
 }
 
-#[serde(rename_all = "lowercase")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 /// The type of a subreddit.
 pub enum SubredditType {
     /// Anyone can post to this subreddit.
@@ -35,8 +35,8 @@ pub enum SubredditType {
     Private,
 }
 
-#[serde(rename_all = "lowercase")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 /// The type of a submission.
 pub enum CommentSort {
     /// Absolute (total karma) ranking.
@@ -49,8 +49,20 @@ pub enum CommentSort {
     New,
 }
 
-#[serde(rename_all = "lowercase")]
+impl CommentSort {
+    /// The value reddit expects in the `sort` query parameter.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            CommentSort::Top => "top",
+            CommentSort::Best => "confidence",
+            CommentSort::Controversial => "controversial",
+            CommentSort::New => "new",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 /// The allowed types of submissions in a Subreddit.
 pub enum SubredditSubmissionType {
     /// All submissions allowed.
@@ -61,8 +73,8 @@ pub enum SubredditSubmissionType {
     Text,
 }
 
-#[serde(rename_all = "lowercase")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 /// The type of a submission.
 pub enum SubmissionType {
     /// URL link.
@@ -71,8 +83,8 @@ pub enum SubmissionType {
     Text,
 }
 
-#[serde(rename_all = "lowercase")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 /// A way to sort links..
 pub enum LinkSort {
     /// Posts made in the past hour.
@@ -89,8 +101,22 @@ pub enum LinkSort {
     All,
 }
 
-#[serde(rename_all = "lowercase")]
+impl LinkSort {
+    /// The value reddit expects in the `t` query parameter.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            LinkSort::Hour => "hour",
+            LinkSort::Day => "day",
+            LinkSort::Week => "week",
+            LinkSort::Month => "month",
+            LinkSort::Year => "year",
+            LinkSort::All => "all",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 /// The type of an award.
 pub enum AwardType {
     /// Can be used anywhere.
@@ -99,8 +125,8 @@ pub enum AwardType {
     Community,
 }
 
-#[serde(rename_all = "UPPERCASE")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
 /// The subtype of an award.
 pub enum AwardSubtype {
     /// Can be used anywhere.
@@ -112,7 +138,7 @@ pub enum AwardSubtype {
 }
 
 /// Parameters for a GET query, a key-value tuple of Strings.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Params(Vec<(String, String)>);
 
 impl Params {
@@ -128,14 +154,167 @@ impl Params {
     }
 }
 
-impl Default for Params {
-    fn default() -> Self {
-        Self(Vec::new())
+/// Options used when requesting the comments of a Link.
+#[derive(Debug, Clone, Default)]
+pub struct CommentOptions {
+    sort: Option<CommentSort>,
+    depth: Option<u64>,
+    limit: Option<u64>,
+}
+
+impl CommentOptions {
+    /// Creates a new CommentOptions struct, using reddit's defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How the comments should be sorted.
+    pub fn sort(mut self, sort: CommentSort) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    /// The maximum depth of the returned comment tree.
+    pub fn depth(mut self, depth: u64) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// The maximum amount of comments to return.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub(crate) fn params(&self) -> Params {
+        let mut params = Params::new();
+        if let Some(sort) = &self.sort {
+            params = params.add("sort", sort.as_str());
+        }
+        if let Some(depth) = self.depth {
+            params = params.add("depth", &depth.to_string());
+        }
+        if let Some(limit) = self.limit {
+            params = params.add("limit", &limit.to_string());
+        }
+        params
+    }
+
+    /// The options used when loading a "continue this thread" placeholder.
+    /// The depth is left out, otherwise reddit cuts the thread off at the same place again.
+    pub(crate) fn thread_params(&self) -> Params {
+        CommentOptions {
+            depth: None,
+            ..self.clone()
+        }
+        .params()
+    }
+
+    /// The subset of the options understood by `/api/morechildren`.
+    pub(crate) fn more_params(&self) -> Params {
+        let mut params = Params::new();
+        if let Some(sort) = &self.sort {
+            params = params.add("sort", sort.as_str());
+        }
+        if let Some(depth) = self.depth {
+            params = params.add("depth", &depth.to_string());
+        }
+        params
+    }
+}
+
+/// Options used when requesting a listing, such as the posts of a subreddit.
+#[derive(Debug, Clone, Default)]
+pub struct ListingOptions {
+    pub(crate) limit: Option<u64>,
+    pub(crate) after: Option<Fullname>,
+    pub(crate) before: Option<Fullname>,
+    pub(crate) count: Option<u64>,
+    show_all: bool,
+    sr_detail: bool,
+    extra: Params,
+}
+
+impl ListingOptions {
+    /// Creates a new ListingOptions struct, using reddit's defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The maximum amount of items to return.
+    /// A single page holds at most 100 items, streams keep fetching pages until the limit is reached.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Only return items after this one.
+    pub fn after(mut self, after: Fullname) -> Self {
+        self.after = Some(after);
+        self
+    }
+
+    /// Only return items before this one, paging backwards through the listing.
+    pub fn before(mut self, before: Fullname) -> Self {
+        self.before = Some(before);
+        self
+    }
+
+    /// The amount of items already seen in this listing.
+    pub fn count(mut self, count: u64) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Show items that would otherwise be hidden by the user's preferences.
+    pub fn show_all(mut self) -> Self {
+        self.show_all = true;
+        self
+    }
+
+    /// Expand the subreddit details of every item.
+    pub fn sr_detail(mut self) -> Self {
+        self.sr_detail = true;
+        self
+    }
+
+    /// The time period to look at, used by the top and controversial listings.
+    pub(crate) fn time(self, time: LinkSort) -> Self {
+        self.param("t", time.as_str())
+    }
+
+    /// A parameter specific to the listing being requested.
+    pub(crate) fn param(mut self, key: &str, value: &str) -> Self {
+        self.extra = self.extra.add(key, value);
+        self
+    }
+
+    pub(crate) fn params(&self) -> Params {
+        let mut params = self.extra.clone();
+        if let Some(limit) = self.limit {
+            params = params.add("limit", &limit.to_string());
+        }
+        if let Some(after) = &self.after {
+            params = params.add("after", after.as_ref());
+        }
+        if let Some(before) = &self.before {
+            params = params.add("before", before.as_ref());
+        }
+        if let Some(count) = self.count {
+            params = params.add("count", &count.to_string());
+        }
+        if self.show_all {
+            params = params.add("show", "all");
+        }
+        if self.sr_detail {
+            params = params.add("sr_detail", "true");
+        }
+        params
     }
 }
 
 /// Fullname is the reddit unique ID for a thing, including the type prefix.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Fullname(String);
 
 impl AsRef<str> for Fullname {
@@ -1,14 +1,76 @@
-use crate::model::misc::Fullname;
+//! Module containing the More struct.
+
+use crate::model::{comment::Comment, listing::Listing, misc::Fullname, thing::Thing};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
 
+/// A placeholder for comments that were not loaded yet.
+/// It can be expanded using `Client::more_children`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct More {
+    /// The total amount of comments hidden behind this placeholder.
     pub count: u64,
+    /// The Fullname of this placeholder.
     pub name: Fullname,
+    /// The ID of this placeholder, `_` if it is a "continue this thread" link.
     pub id: String,
+    /// The Fullname of the comment or link the hidden comments belong to.
     pub parent_id: Fullname,
+    /// The depth of the hidden comments.
     pub depth: u64,
+    /// The IDs of the hidden comments, empty for "continue this thread" links.
     pub children: Vec<String>,
+    /// The fullname after this placeholder.
     pub after: Option<String>,
+    /// The fullname before this placeholder.
     pub before: Option<String>,
 }
+
+/// Takes comments out of their nested replies, collecting the placeholders found on the way.
+pub(crate) fn flatten(things: Vec<Thing>, comments: &mut Vec<Comment>, more: &mut VecDeque<More>) {
+    for thing in things {
+        match thing {
+            Thing::Comment(mut comment) => {
+                let replies = comment.replies.take();
+                comments.push(comment);
+                if let Some(replies) = replies {
+                    if let Thing::Listing(listing) = *replies {
+                        flatten(listing.children, comments, more);
+                    }
+                }
+            }
+            Thing::More(m) => more.push_back(m),
+            _ => {}
+        }
+    }
+}
+
+/// Nests a flat list of comments under their parents, returning the children of `root`.
+pub(crate) fn assemble(comments: Vec<Comment>, root: &Fullname) -> Vec<Comment> {
+    let mut seen: HashSet<Fullname> = HashSet::new();
+    let mut children: HashMap<Fullname, Vec<Comment>> = HashMap::new();
+    for comment in comments {
+        if seen.insert(comment.name.clone()) {
+            children
+                .entry(comment.parent_id.clone())
+                .or_default()
+                .push(comment);
+        }
+    }
+    build(&mut children, root)
+}
+
+fn build(children: &mut HashMap<Fullname, Vec<Comment>>, parent: &Fullname) -> Vec<Comment> {
+    let mut level = children.remove(parent).unwrap_or_default();
+    for comment in level.iter_mut() {
+        let replies = build(children, &comment.name);
+        if !replies.is_empty() {
+            let listing = Listing {
+                children: replies.into_iter().map(Thing::Comment).collect(),
+                ..Listing::default()
+            };
+            comment.replies = Some(Box::new(Thing::Listing(listing)));
+        }
+    }
+    level
+}
@@ -1,6 +1,13 @@
 //! All of the structs you may need when handling the returned JSON.
 
 pub use crate::model::{
-    award::Award, comment::Comment, link::Link, message::Message, subreddit::Subreddit, user::User,
+    award::Award,
+    comment::Comment,
+    commenttree::CommentTree,
+    link::Link,
+    message::{InboxItem, Message},
+    more::More,
+    subreddit::Subreddit,
+    user::User,
     usersubreddit::UserSubreddit,
 };
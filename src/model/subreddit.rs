@@ -6,12 +6,14 @@ use crate::{
     client::Client,
     error::Error,
     model::{
+        comment::Comment,
         link::Link,
         misc::{
-            CommentSort, Fullname, Params, QuarantinePermissions, SubredditSubmissionType,
-            SubredditType,
+            CommentSort, Fullname, LinkSort, ListingOptions, Params, QuarantinePermissions,
+            SubredditSubmissionType, SubredditType,
         },
     },
+    stream::ListingStream,
 };
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
@@ -71,17 +73,25 @@ pub struct Subreddit {
 
 impl Subreddit {
     /// Returns the hot posts in a given subreddit.
-    pub async fn hot<T: Auth + Send + Sync>(&self, client: &Client<T>) -> Result<Vec<Link>, Error> {
+    pub async fn hot<T: Auth + Send + Sync>(
+        &self,
+        client: &Client<T>,
+        options: &ListingOptions,
+    ) -> Result<Vec<Link>, Error> {
         client
-            .get_posts(Route::SubredditHot(self.display_name.clone()))
+            .get_items(Route::SubredditHot(self.display_name.clone()), options)
             .await
     }
 
     /// Returns the new posts in a given subreddit.
     #[allow(clippy::new_ret_no_self)]
-    pub async fn new<T: Auth + Send + Sync>(&self, client: &Client<T>) -> Result<Vec<Link>, Error> {
+    pub async fn new<T: Auth + Send + Sync>(
+        &self,
+        client: &Client<T>,
+        options: &ListingOptions,
+    ) -> Result<Vec<Link>, Error> {
         client
-            .get_posts(Route::SubredditNew(self.display_name.clone()))
+            .get_items(Route::SubredditNew(self.display_name.clone()), options)
             .await
     }
 
@@ -89,12 +99,134 @@ impl Subreddit {
     pub async fn rising<T: Auth + Send + Sync>(
         &self,
         client: &Client<T>,
+        options: &ListingOptions,
+    ) -> Result<Vec<Link>, Error> {
+        client
+            .get_items(Route::SubredditRising(self.display_name.clone()), options)
+            .await
+    }
+
+    /// Returns the top posts in a given subreddit within the given time period.
+    pub async fn top<T: Auth + Send + Sync>(
+        &self,
+        client: &Client<T>,
+        time: LinkSort,
+        options: &ListingOptions,
+    ) -> Result<Vec<Link>, Error> {
+        client
+            .get_items(
+                Route::SubredditTop(self.display_name.clone()),
+                &options.clone().time(time),
+            )
+            .await
+    }
+
+    /// Returns the controversial posts in a given subreddit within the given time period.
+    pub async fn controversial<T: Auth + Send + Sync>(
+        &self,
+        client: &Client<T>,
+        time: LinkSort,
+        options: &ListingOptions,
     ) -> Result<Vec<Link>, Error> {
         client
-            .get_posts(Route::SubredditRising(self.display_name.clone()))
+            .get_items(
+                Route::SubredditControversial(self.display_name.clone()),
+                &options.clone().time(time),
+            )
+            .await
+    }
+
+    /// Returns the newest comments in a given subreddit.
+    pub async fn comments<T: Auth + Send + Sync>(
+        &self,
+        client: &Client<T>,
+        options: &ListingOptions,
+    ) -> Result<Vec<Comment>, Error> {
+        client
+            .get_items(Route::SubredditComments(self.display_name.clone()), options)
             .await
     }
 
+    /// Returns a stream over the hot posts in a given subreddit, fetching more pages as needed.
+    pub fn hot_stream<'a, T: Auth + Send + Sync>(
+        &self,
+        client: &'a Client<T>,
+        options: &ListingOptions,
+    ) -> ListingStream<'a, Link> {
+        ListingStream::new(
+            client,
+            Route::SubredditHot(self.display_name.clone()),
+            options.clone(),
+        )
+    }
+
+    /// Returns a stream over the new posts in a given subreddit, fetching more pages as needed.
+    pub fn new_stream<'a, T: Auth + Send + Sync>(
+        &self,
+        client: &'a Client<T>,
+        options: &ListingOptions,
+    ) -> ListingStream<'a, Link> {
+        ListingStream::new(
+            client,
+            Route::SubredditNew(self.display_name.clone()),
+            options.clone(),
+        )
+    }
+
+    /// Returns a stream over the rising posts in a given subreddit, fetching more pages as needed.
+    pub fn rising_stream<'a, T: Auth + Send + Sync>(
+        &self,
+        client: &'a Client<T>,
+        options: &ListingOptions,
+    ) -> ListingStream<'a, Link> {
+        ListingStream::new(
+            client,
+            Route::SubredditRising(self.display_name.clone()),
+            options.clone(),
+        )
+    }
+
+    /// Returns a stream over the top posts in a given subreddit within the given time period.
+    pub fn top_stream<'a, T: Auth + Send + Sync>(
+        &self,
+        client: &'a Client<T>,
+        time: LinkSort,
+        options: &ListingOptions,
+    ) -> ListingStream<'a, Link> {
+        ListingStream::new(
+            client,
+            Route::SubredditTop(self.display_name.clone()),
+            options.clone().time(time),
+        )
+    }
+
+    /// Returns a stream over the controversial posts in a given subreddit within the given time period.
+    pub fn controversial_stream<'a, T: Auth + Send + Sync>(
+        &self,
+        client: &'a Client<T>,
+        time: LinkSort,
+        options: &ListingOptions,
+    ) -> ListingStream<'a, Link> {
+        ListingStream::new(
+            client,
+            Route::SubredditControversial(self.display_name.clone()),
+            options.clone().time(time),
+        )
+    }
+
+    /// Returns a stream over the newest comments in a given subreddit, fetching more pages as needed.
+    pub fn comments_stream<'a, T: Auth + Send + Sync>(
+        &self,
+        client: &'a Client<T>,
+        options: &ListingOptions,
+    ) -> ListingStream<'a, Comment> {
+        ListingStream::new(
+            client,
+            Route::SubredditComments(self.display_name.clone()),
+            options.clone(),
+        )
+    }
+
     /// Post to a subreddit.
     pub fn submit_text<'a, T: Auth + Send + Sync>(
         &self,
@@ -169,10 +301,13 @@ impl<'a, T: Auth + Send + Sync> SubmitBuilder<'a, T> {
         self
     }
 
-    /// Send the post.
-    pub async fn send(&self) -> Result<(), Error> {
-        println!("{:?}", &self.params);
-        self.client.post(Route::Submit, &self.params).await?;
-        Ok(())
+    /// Send the post, then fetch the created Link.
+    pub async fn send(&self) -> Result<Link, Error> {
+        let response = self.client.post(Route::Submit, &self.params).await?;
+        let id = response
+            .pointer("/json/data/id")
+            .and_then(|v| v.as_str())
+            .ok_or("No `id` field in response")?;
+        self.client.link(id).await
     }
 }
@@ -2,8 +2,14 @@
 
 use crate::error::Error;
 use crate::model::{
-    award::Award, comment::Comment, link::Link, listing::Listing, message::Message, more::More,
-    subreddit::Subreddit, user::User,
+    award::Award,
+    comment::Comment,
+    link::Link,
+    listing::Listing,
+    message::{InboxItem, Message},
+    more::More,
+    subreddit::Subreddit,
+    user::User,
 };
 use serde::de::Error as DeError;
 use serde::{Deserialize, Deserializer, Serialize};
@@ -48,6 +54,16 @@ impl<'de> Deserialize<'de> for Thing {
             .ok_or_else(|| DeError::custom("expected data field"))?;
 
         Ok(match kind {
+            // Comment replies and username mentions in the inbox are shaped like messages.
+            "t1" if data.get("was_comment").is_some() => {
+                let value: Message = serde_json::from_value(data.clone()).map_err(|e| {
+                    DeError::custom(format!(
+                        "failed to deserialize thing data into message: {}",
+                        e
+                    ))
+                })?;
+                Thing::Message(value)
+            }
             "t1" => {
                 let mut data = data.clone();
                 if let Some(x) = data.get_mut("replies") {
@@ -170,6 +186,25 @@ impl TryFrom<Thing> for Link {
     }
 }
 
+impl TryFrom<Thing> for Message {
+    type Error = Error;
+    fn try_from(value: Thing) -> Result<Self, Self::Error> {
+        match value {
+            Thing::Message(m) => Ok(m),
+            _ => Err(Error::Serde(DeError::custom(
+                "failed to convert Thing to Message",
+            ))),
+        }
+    }
+}
+
+impl TryFrom<Thing> for InboxItem {
+    type Error = Error;
+    fn try_from(value: Thing) -> Result<Self, Self::Error> {
+        Message::try_from(value).map(InboxItem::from)
+    }
+}
+
 impl TryFrom<Thing> for Listing {
     type Error = Error;
     fn try_from(value: Thing) -> Result<Self, Self::Error> {
@@ -4,7 +4,13 @@ use crate::{
     auth::Auth,
     client::{route::Route, Client},
     error::Error,
-    model::{misc::Fullname, misc::Params, usersubreddit::UserSubreddit},
+    model::{
+        comment::Comment,
+        link::Link,
+        misc::{Fullname, ListingOptions, Params},
+        usersubreddit::UserSubreddit,
+    },
+    stream::ListingStream,
 };
 use serde::{Deserialize, Serialize};
 
@@ -99,4 +105,30 @@ impl User {
             .await
             .and(Ok(()))
     }
+
+    /// Returns a stream over the posts submitted by this User, fetching more pages as needed.
+    pub fn submitted<'a, T: Auth + Send + Sync>(
+        &self,
+        client: &'a Client<T>,
+        options: &ListingOptions,
+    ) -> ListingStream<'a, Link> {
+        ListingStream::new(
+            client,
+            Route::UserSubmitted(self.name.clone()),
+            options.clone(),
+        )
+    }
+
+    /// Returns a stream over the comments made by this User, fetching more pages as needed.
+    pub fn comments<'a, T: Auth + Send + Sync>(
+        &self,
+        client: &'a Client<T>,
+        options: &ListingOptions,
+    ) -> ListingStream<'a, Comment> {
+        ListingStream::new(
+            client,
+            Route::UserComments(self.name.clone()),
+            options.clone(),
+        )
+    }
 }
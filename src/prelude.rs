@@ -1,5 +1,9 @@
 //! The basic exports you are most likely to need in your application.
 
+pub use crate::auth::application::ApplicationAuth;
 pub use crate::auth::basic::BasicAuth;
+pub use crate::auth::scope::Scope;
+pub use crate::auth::web::WebAuth;
+pub use crate::client::builder::ClientBuilder;
 pub use crate::client::Client;
-pub use crate::error::Error;
+pub use crate::error::{ApiError, Error};
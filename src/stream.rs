@@ -0,0 +1,427 @@
+//! Streams over reddit listings.
+
+use crate::{
+    auth::Auth,
+    client::{route::Route, Client},
+    error::Error,
+    model::{
+        comment::Comment,
+        link::Link,
+        message::InboxItem,
+        misc::{Fullname, ListingOptions, Params},
+        thing::Thing,
+    },
+};
+use futures::stream::{self, Stream, StreamExt};
+use futures_timer::Delay;
+use std::collections::{HashSet, VecDeque};
+use std::convert::TryFrom;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// The maximum amount of items reddit returns in a single page.
+const PAGE_SIZE: u64 = 100;
+
+/// A lazy stream over the items of a listing, fetching the next page only when it is needed.
+pub struct ListingStream<'a, R> {
+    inner: Pin<Box<dyn Stream<Item = Result<R, Error>> + Send + 'a>>,
+}
+
+struct Page<'a, T: Auth + Send + Sync> {
+    client: &'a Client<T>,
+    route: Route,
+    options: ListingOptions,
+    buffer: VecDeque<Thing>,
+    remaining: Option<u64>,
+    seen: u64,
+    done: bool,
+}
+
+impl<'a, R> ListingStream<'a, R>
+where
+    R: TryFrom<Thing, Error = Error> + Send + 'a,
+{
+    /// Follows the `after` cursor of the listing, or the `before` cursor if the options start with one.
+    pub(crate) fn new<T: Auth + Send + Sync>(
+        client: &'a Client<T>,
+        route: Route,
+        options: ListingOptions,
+    ) -> Self {
+        let state = Page {
+            client,
+            route,
+            remaining: options.limit,
+            seen: 0,
+            options,
+            buffer: VecDeque::new(),
+            done: false,
+        };
+
+        let inner = stream::try_unfold(state, |mut state| async move {
+            loop {
+                if state.remaining == Some(0) {
+                    return Ok(None);
+                }
+                if let Some(thing) = state.buffer.pop_front() {
+                    state.remaining = state.remaining.map(|r| r - 1);
+                    let item = R::try_from(thing)?;
+                    return Ok(Some((item, state)));
+                }
+                if state.done {
+                    return Ok(None);
+                }
+                state.next_page().await?;
+            }
+        });
+
+        ListingStream {
+            inner: Box::pin(inner),
+        }
+    }
+}
+
+impl<'a, T: Auth + Send + Sync> Page<'a, T> {
+    async fn next_page(&mut self) -> Result<(), Error> {
+        let backwards = self.options.before.is_some();
+        let mut options = self.options.clone();
+        options.limit = Some(self.remaining.map_or(PAGE_SIZE, |r| r.min(PAGE_SIZE)));
+        options.count = Some(self.options.count.unwrap_or(0) + self.seen);
+
+        let listing = self
+            .client
+            .get_listing(self.route.clone(), &options.params())
+            .await?;
+        self.seen += listing.children.len() as u64;
+        if listing.children.is_empty() {
+            self.done = true;
+        }
+
+        if backwards {
+            // Pages are always returned newest first, so they have to be reversed to keep moving away from the cursor.
+            self.buffer.extend(listing.children.into_iter().rev());
+            self.options.before = listing.before;
+            self.done |= self.options.before.is_none();
+        } else {
+            self.buffer.extend(listing.children);
+            self.options.after = listing.after;
+            self.done |= self.options.after.is_none();
+        }
+        Ok(())
+    }
+}
+
+impl<'a, R> Stream for ListingStream<'a, R> {
+    type Item = Result<R, Error>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+impl<'a, R> fmt::Debug for ListingStream<'a, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ListingStream").finish()
+    }
+}
+
+/// Options controlling how a polling stream checks for new items.
+#[derive(Debug, Clone)]
+pub struct PollOptions {
+    min_delay: Duration,
+    max_delay: Duration,
+    skip_existing: bool,
+    capacity: usize,
+}
+
+impl Default for PollOptions {
+    fn default() -> Self {
+        PollOptions {
+            min_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(16),
+            skip_existing: false,
+            capacity: 1000,
+        }
+    }
+}
+
+impl PollOptions {
+    /// Creates a new PollOptions struct with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The delay between polls while new items keep coming in, one second by default.
+    pub fn min_delay(mut self, delay: Duration) -> Self {
+        self.min_delay = delay;
+        self
+    }
+
+    /// The longest delay between polls, reached when nothing new was found for a while. 16 seconds by default.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Do not return the items that already existed when the stream was started.
+    pub fn skip_existing(mut self) -> Self {
+        self.skip_existing = true;
+        self
+    }
+
+    /// How many Fullnames to remember for skipping duplicates, 1000 by default.
+    pub fn seen_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+}
+
+/// Items that can be deduplicated by their Fullname.
+pub(crate) trait Named {
+    fn fullname(&self) -> &Fullname;
+}
+
+impl Named for Link {
+    fn fullname(&self) -> &Fullname {
+        &self.name
+    }
+}
+
+impl Named for Comment {
+    fn fullname(&self) -> &Fullname {
+        &self.name
+    }
+}
+
+impl Named for InboxItem {
+    fn fullname(&self) -> &Fullname {
+        &self.message().name
+    }
+}
+
+/// A set of Fullnames that forgets the oldest entries once it is full.
+#[derive(Debug)]
+struct SeenSet {
+    order: VecDeque<Fullname>,
+    set: HashSet<Fullname>,
+    capacity: usize,
+}
+
+impl SeenSet {
+    fn new(capacity: usize) -> Self {
+        SeenSet {
+            order: VecDeque::new(),
+            set: HashSet::new(),
+            // A single page must always fit, otherwise items would be returned again.
+            capacity: capacity.max(2 * PAGE_SIZE as usize),
+        }
+    }
+
+    /// Returns true if the Fullname was not seen before.
+    fn insert(&mut self, name: &Fullname) -> bool {
+        if !self.set.insert(name.clone()) {
+            return false;
+        }
+        self.order.push_back(name.clone());
+        if self.order.len() > self.capacity {
+            if let Some(old) = self.order.pop_front() {
+                self.set.remove(&old);
+            }
+        }
+        true
+    }
+}
+
+/// A never-ending stream of the newest items of a listing, polling reddit for new ones.
+/// Each item is only returned once, and network errors are retried after waiting.
+pub struct PollStream<'a, R> {
+    inner: Pin<Box<dyn Stream<Item = Result<R, Error>> + Send + 'a>>,
+}
+
+struct Poller<'a, T: Auth + Send + Sync, R> {
+    client: &'a Client<T>,
+    route: Route,
+    params: Params,
+    options: PollOptions,
+    seen: SeenSet,
+    buffer: VecDeque<R>,
+    delay: Duration,
+    polled: bool,
+    skipping: bool,
+}
+
+impl<'a, R: Send + 'a> PollStream<'a, R> {
+    pub(crate) fn new<T: Auth + Send + Sync>(
+        client: &'a Client<T>,
+        route: Route,
+        params: Params,
+        options: PollOptions,
+    ) -> Self
+    where
+        R: TryFrom<Thing, Error = Error> + Named,
+    {
+        let state = Poller {
+            client,
+            route,
+            params: params.add("limit", &PAGE_SIZE.to_string()),
+            seen: SeenSet::new(options.capacity),
+            delay: options.min_delay,
+            skipping: options.skip_existing,
+            options,
+            buffer: VecDeque::new(),
+            polled: false,
+        };
+
+        let inner = stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(item) = state.buffer.pop_front() {
+                    return Some((Ok(item), state));
+                }
+                if state.polled {
+                    Delay::new(state.delay).await;
+                }
+                state.polled = true;
+                match state.poll().await {
+                    Ok(0) => state.delay = (state.delay * 2).min(state.options.max_delay),
+                    Ok(_) => state.delay = state.options.min_delay,
+                    Err(Error::Transport(_)) => {
+                        state.delay = (state.delay * 2).min(state.options.max_delay)
+                    }
+                    Err(Error::Status(status)) if status == 429 || status >= 500 => {
+                        state.delay = (state.delay * 2).min(state.options.max_delay)
+                    }
+                    Err(e) => return Some((Err(e), state)),
+                }
+            }
+        });
+
+        PollStream {
+            inner: Box::pin(inner),
+        }
+    }
+}
+
+impl<'a, T: Auth + Send + Sync, R> Poller<'a, T, R>
+where
+    R: TryFrom<Thing, Error = Error> + Named,
+{
+    /// Fetches the newest page, returning how many new items were found.
+    async fn poll(&mut self) -> Result<usize, Error> {
+        let listing = self
+            .client
+            .get_listing(self.route.clone(), &self.params)
+            .await?;
+        let skip = self.skipping;
+        self.skipping = false;
+
+        let mut found = 0;
+        // Listings are newest first, the stream returns them oldest first.
+        for thing in listing.children.into_iter().rev() {
+            let item = R::try_from(thing)?;
+            if self.seen.insert(item.fullname()) {
+                found += 1;
+                if !skip {
+                    self.buffer.push_back(item);
+                }
+            }
+        }
+        Ok(found)
+    }
+}
+
+impl<'a, R> Stream for PollStream<'a, R> {
+    type Item = Result<R, Error>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+impl<'a, R> fmt::Debug for PollStream<'a, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PollStream").finish()
+    }
+}
+
+/// A never-ending stream of unread inbox items, see `Client::inbox_stream`.
+pub struct InboxStream<'a> {
+    inner: Pin<Box<dyn Stream<Item = Result<InboxItem, Error>> + Send + 'a>>,
+    /// The last returned item, marked as read once it was handled.
+    handled: Arc<Mutex<Option<InboxItem>>>,
+}
+
+impl<'a> InboxStream<'a> {
+    pub(crate) fn new<T: Auth + Send + Sync>(
+        client: &'a Client<T>,
+        mark_read: bool,
+        options: PollOptions,
+    ) -> Self {
+        let unread: PollStream<'a, InboxItem> = PollStream::new(
+            client,
+            Route::MessageUnread,
+            Params::new().add("mark", "false"),
+            options,
+        );
+        let handled = Arc::new(Mutex::new(None));
+
+        let shared = handled.clone();
+        let inner = stream::unfold(unread, move |mut unread| {
+            let handled = shared.clone();
+            async move {
+                // Asking for the next item means the previous one was handled successfully.
+                if let Err(e) = mark_handled(client, &handled).await {
+                    return Some((Err(e), unread));
+                }
+                let result = unread.next().await?;
+                if let (true, Ok(item)) = (mark_read, &result) {
+                    *lock(&handled) = Some(item.clone());
+                }
+                Some((result, unread))
+            }
+        });
+
+        InboxStream {
+            inner: Box::pin(inner),
+            handled,
+        }
+    }
+
+    /// Marks the last returned item as read right away, instead of once the next item is requested.
+    /// Call it after handling the last item before dropping the stream, or it stays unread.
+    /// If marking fails, the item is kept and tried again with the next item.
+    pub async fn mark_read<T: Auth + Send + Sync>(&self, client: &Client<T>) -> Result<(), Error> {
+        mark_handled(client, &self.handled).await
+    }
+}
+
+fn lock(handled: &Mutex<Option<InboxItem>>) -> MutexGuard<'_, Option<InboxItem>> {
+    handled.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Marks the handled item as read, putting it back if that fails.
+async fn mark_handled<T: Auth + Send + Sync>(
+    client: &Client<T>,
+    handled: &Mutex<Option<InboxItem>>,
+) -> Result<(), Error> {
+    let item = lock(handled).take();
+    if let Some(item) = item {
+        if let Err(e) = item.message().mark_read(client).await {
+            *lock(handled) = Some(item);
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+impl<'a> Stream for InboxStream<'a> {
+    type Item = Result<InboxItem, Error>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+impl<'a> fmt::Debug for InboxStream<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InboxStream").finish()
+    }
+}
@@ -0,0 +1,206 @@
+//! A minimal HTTP server standing in for reddit in the client tests.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tranquility::client::{endpoints::Endpoints, retry::RetryPolicy};
+use tranquility::prelude::*;
+
+/// A request received by the mock server.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+/// The response the mock server sends back.
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    pub fn json(body: &str) -> Self {
+        Response {
+            status: 200,
+            headers: vec![
+                ("x-ratelimit-used".into(), "1".into()),
+                ("x-ratelimit-remaining".into(), "599.0".into()),
+                ("x-ratelimit-reset".into(), "300".into()),
+            ],
+            body: body.to_string(),
+        }
+    }
+
+    pub fn without_headers(mut self) -> Self {
+        self.headers.clear();
+        self
+    }
+
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// The token response for a script application.
+pub fn token_response(access_token: &str) -> Response {
+    Response::json(&format!(
+        r#"{{"access_token": "{}", "token_type": "bearer", "expires_in": 3600, "scope": "*"}}"#,
+        access_token
+    ))
+}
+
+/// A link in the shape reddit lists it, with only the fields the model needs.
+pub fn link(id: &str) -> String {
+    format!(
+        r#"{{"kind": "t3", "data": {{"title": "{}", "author": "someone", "score": 1, "num_comments": 0, "subreddit": "pcgaming", "name": "t3_{}", "all_awardings": []}}}}"#,
+        id, id
+    )
+}
+
+/// A listing of the given Things, continuing after `after` if there is one.
+pub fn listing(children: &[String], after: Option<&str>) -> Response {
+    let after = after.map_or("null".to_string(), |a| format!(r#""{}""#, a));
+    Response::json(&format!(
+        r#"{{"kind": "Listing", "data": {{"modhash": null, "dist": {}, "children": [{}], "after": {}, "before": null}}}}"#,
+        children.len(),
+        children.join(","),
+        after
+    ))
+}
+
+pub fn fixture(name: &str) -> String {
+    std::fs::read_to_string(format!("./tests/json/{}.json", name)).expect("Failed to read file.")
+}
+
+/// A server answering every request with the handler, remembering the requests it received.
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    pub fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                if let Some(request) = read_request(&stream) {
+                    let response = handler(&request);
+                    received.lock().unwrap().push(request);
+                    write_response(&mut stream, &response);
+                }
+            }
+        });
+
+        MockServer { url, requests }
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn read_request(stream: &TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?.to_string();
+    let (path, query) = match target.find('?') {
+        Some(i) => (target[..i].to_string(), target[i + 1..].to_string()),
+        None => (target, String::new()),
+    };
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).ok()?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(i) = header.find(':') {
+            headers.insert(
+                header[..i].to_lowercase(),
+                header[i + 1..].trim().to_string(),
+            );
+        }
+    }
+
+    let length = headers
+        .get("content-length")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(Request {
+        method,
+        path,
+        query,
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response) {
+    let mut head = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(response.body.as_bytes());
+}
+
+/// Endpoints sending every request to the mock server.
+pub fn endpoints(server: &MockServer) -> Endpoints {
+    Endpoints::new().oauth(&server.url).www(&server.url)
+}
+
+/// A client logged in to the mock server with the default retry policy.
+pub async fn client(server: &MockServer) -> Client<BasicAuth> {
+    client_with_retries(server, RetryPolicy::new()).await
+}
+
+/// A client logged in to the mock server, retrying failed requests as the policy says.
+pub async fn client_with_retries(server: &MockServer, retry: RetryPolicy) -> Client<BasicAuth> {
+    let auth = BasicAuth::new("id", "secret", "user", "password").await;
+    ClientBuilder::new("tranquility tests")
+        .endpoints(endpoints(server))
+        .retry_policy(retry)
+        .build(auth)
+        .await
+        .expect("Failed to log in")
+}
@@ -0,0 +1,160 @@
+use futures::executor::block_on;
+use std::time::SystemTime;
+use tranquility::auth::{
+    scope::Scope,
+    store::{FileStore, MemoryStore, TokenStore},
+    totp::Totp,
+    Token, TokenCache,
+};
+use tranquility::client::{endpoints::Endpoints, transport::ReqwestTransport};
+
+fn token(scopes: Vec<Scope>) -> Token {
+    Token {
+        access_token: "token".into(),
+        refresh_token: None,
+        expires_at: SystemTime::now(),
+        scopes,
+    }
+}
+
+#[test]
+fn test_scope_parse() {
+    let scopes = Scope::parse_list("identity read modposts unknownscope");
+    let unknown = Scope::Other("unknownscope".into());
+    assert_eq!(
+        scopes,
+        vec![
+            Scope::Identity,
+            Scope::Read,
+            Scope::ModPosts,
+            unknown.clone()
+        ]
+    );
+    assert_eq!(Scope::parse_list("*"), vec![Scope::All]);
+    assert_eq!(Scope::join(&scopes), "identity read modposts unknownscope");
+    assert!("privatemessages".parse::<Scope>().is_ok());
+    assert!("".parse::<Scope>().is_err());
+
+    // A token granted only unknown scopes is not mistaken for one without any reported.
+    let unknown_only = token(Scope::parse_list("unknownscope"));
+    assert!(!unknown_only.has_scope(&Scope::Read));
+    assert!(unknown_only.has_scope(&unknown));
+}
+
+#[test]
+fn test_token_has_scope() {
+    let limited = token(vec![Scope::Read, Scope::Submit]);
+    assert!(limited.has_scope(&Scope::Read));
+    assert!(!limited.has_scope(&Scope::ModPosts));
+    assert!(token(vec![Scope::All]).has_scope(&Scope::ModPosts));
+    assert!(token(Vec::new()).has_scope(&Scope::ModPosts));
+}
+
+#[test]
+fn test_memory_store_shared() {
+    let store = MemoryStore::new();
+    let shared = store.clone();
+    block_on(store.save(&token(vec![Scope::Read]))).unwrap();
+    let loaded = block_on(shared.load()).unwrap().expect("No token saved");
+    assert_eq!(loaded.access_token, "token");
+    block_on(shared.clear()).unwrap();
+    assert!(block_on(store.load()).unwrap().is_none());
+}
+
+#[test]
+fn test_file_store() {
+    let path = std::env::temp_dir().join(format!("tranquility-token-{}.json", std::process::id()));
+    let store = FileStore::new(&path);
+    assert!(block_on(store.load()).unwrap().is_none());
+
+    let mut saved = token(vec![
+        Scope::All,
+        Scope::ModPosts,
+        Scope::Other("newscope".into()),
+    ]);
+    saved.refresh_token = Some("refresh".into());
+    block_on(store.save(&saved)).unwrap();
+    let loaded = block_on(store.load()).unwrap().expect("No token saved");
+    assert_eq!(loaded.refresh_token.as_deref(), Some("refresh"));
+    assert_eq!(loaded.scopes, saved.scopes);
+    assert_eq!(loaded.expires_at, saved.expires_at);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    block_on(store.clear()).unwrap();
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_totp_rfc6238() {
+    // The SHA1 test vectors from RFC 6238, using the ASCII secret "12345678901234567890".
+    let totp = Totp::new("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ")
+        .unwrap()
+        .digits(8)
+        .unwrap();
+    assert_eq!(totp.code_at(59), "94287082");
+    assert_eq!(totp.code_at(1111111109), "07081804");
+    assert_eq!(totp.code_at(1111111111), "14050471");
+    assert_eq!(totp.code_at(1234567890), "89005924");
+    assert_eq!(totp.code_at(2000000000), "69279037");
+    assert_eq!(totp.code_at(20000000000), "65353130");
+}
+
+#[test]
+fn test_totp_secret() {
+    let spaced = Totp::new("gezd gnbv gy3t qojq gezd gnbv gy3t qojq").unwrap();
+    assert_eq!(spaced.code_at(59), "287082");
+    assert!(Totp::new("not base32!").is_err());
+}
+
+#[test]
+fn test_totp_digits() {
+    let totp = Totp::new("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ").unwrap();
+    assert_eq!(totp.clone().digits(7).unwrap().code_at(59), "4287082");
+    assert!(totp.clone().digits(5).is_err());
+    assert!(totp.clone().digits(9).is_err());
+    assert!(totp.digits(10).is_err());
+}
+
+#[test]
+fn test_logout_clears_store() {
+    let store = MemoryStore::new();
+    block_on(store.save(&token(vec![Scope::Read]))).unwrap();
+    let mut cache = TokenCache::new();
+    cache.set_store(store.clone());
+
+    block_on(cache.logout(&ReqwestTransport::new(), &Endpoints::new(), "id", "secret")).unwrap();
+    assert!(block_on(store.load()).unwrap().is_none());
+    assert!(block_on(cache.get()).is_none());
+}
+
+#[test]
+fn test_file_store_concurrent_saves() {
+    let dir = std::env::temp_dir().join(format!("tranquility-store-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let store = FileStore::new(dir.join("token.json"));
+
+    let writers: Vec<_> = (0..8)
+        .map(|_| {
+            let store = store.clone();
+            std::thread::spawn(move || {
+                for _ in 0..20 {
+                    block_on(store.save(&token(vec![Scope::Read]))).unwrap();
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+
+    // Every save renamed its own temporary file, none are left behind.
+    let loaded = block_on(store.load()).unwrap().expect("No token saved");
+    assert_eq!(loaded.scopes, vec![Scope::Read]);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
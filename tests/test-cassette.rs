@@ -0,0 +1,162 @@
+mod common;
+
+use common::{fixture, token_response, MockServer, Response};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tranquility::client::{cassette::Cassette, endpoints::Endpoints, transport::ReqwestTransport};
+use tranquility::prelude::*;
+
+async fn client(transport: Cassette, url: &str) -> Result<Client<BasicAuth>, Error> {
+    let auth = BasicAuth::new("id", "secret", "user", "hunter2").await;
+    ClientBuilder::new("tranquility tests")
+        .endpoints(Endpoints::new().oauth(url).www(url))
+        .transport(transport)
+        .build(auth)
+        .await
+}
+
+fn cassette_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "tranquility-cassette-{}-{}.json",
+        name,
+        std::process::id()
+    ))
+}
+
+#[tokio::test]
+async fn test_record_and_replay() {
+    let path = cassette_path("basic");
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("secret-token"),
+        "/user/spez/about" => Response::json(&fixture("user-about")),
+        _ => Response::json("{}").status(404),
+    });
+
+    let recorder = client(
+        Cassette::record(&path, ReqwestTransport::new()),
+        &server.url,
+    )
+    .await
+    .unwrap();
+    assert_eq!(recorder.user("spez").await.unwrap().name, "spez");
+    assert!(recorder.user("nobody").await.is_err());
+
+    let cassette = std::fs::read_to_string(&path).unwrap();
+    assert!(!cassette.contains("hunter2"));
+    assert!(!cassette.contains("secret-token"));
+    assert!(!cassette.contains("Bearer"));
+    assert!(cassette.contains("/user/spez/about"));
+
+    // Replayed against a host that does not exist, so nothing can reach the network.
+    let replayer = client(Cassette::replay(&path).unwrap(), "http://replay.invalid")
+        .await
+        .unwrap();
+    assert_eq!(replayer.user("spez").await.unwrap().name, "spez");
+    assert!(matches!(
+        replayer.user("nobody").await,
+        Err(Error::Status(404))
+    ));
+    // Requests that were never recorded fail instead of being sent.
+    assert!(matches!(
+        replayer.user("other").await,
+        Err(Error::Custom(_))
+    ));
+    assert_eq!(server.requests().len(), 3);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+async fn web_client(transport: Cassette, url: &str, code: &str) -> Client<WebAuth> {
+    let auth = WebAuth::new("id", "secret", "http://localhost/callback", code).await;
+    ClientBuilder::new("tranquility tests")
+        .endpoints(Endpoints::new().oauth(url).www(url))
+        .transport(transport)
+        .build(auth)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_form_secrets() {
+    let path = cassette_path("web");
+    let server = MockServer::start(|request| match request.path.as_str() {
+        // Expiring within a minute, so the first request refreshes it.
+        "/api/v1/access_token" => Response::json(
+            r#"{"access_token": "access-secret", "refresh_token": "refresh-secret", "token_type": "bearer", "expires_in": 30, "scope": "*"}"#,
+        ),
+        "/user/spez/about" => Response::json(&fixture("user-about")),
+        _ => Response::json("{}").status(404),
+    });
+
+    let recorder = web_client(
+        Cassette::record(&path, ReqwestTransport::new()),
+        &server.url,
+        "code-secret",
+    )
+    .await;
+    recorder.user("spez").await.unwrap();
+    let bodies: Vec<String> = server.requests().into_iter().map(|r| r.body).collect();
+    assert!(bodies[0].contains("code=code-secret"));
+    assert!(bodies[1].contains("refresh_token=refresh-secret"));
+
+    let cassette = std::fs::read_to_string(&path).unwrap();
+    for secret in &["code-secret", "refresh-secret", "access-secret"] {
+        assert!(!cassette.contains(secret));
+    }
+    assert!(cassette.contains("authorization_code"));
+    assert!(cassette.contains("refresh_token"));
+
+    // The redacted form parameters still match, whatever the code is this time.
+    let replayer = web_client(
+        Cassette::replay(&path).unwrap(),
+        "http://replay.invalid",
+        "another-code",
+    )
+    .await;
+    assert_eq!(replayer.user("spez").await.unwrap().name, "spez");
+    assert_eq!(server.requests().len(), 3);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_replay_order() {
+    let path = cassette_path("order");
+    let calls = AtomicUsize::new(0);
+    let server = MockServer::start(move |request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first"),
+        "/user/spez/about" if calls.fetch_add(1, Ordering::SeqCst) == 1 => {
+            Response::json("{}").status(404)
+        }
+        "/user/spez/about" => Response::json(&fixture("user-about")),
+        _ => Response::json("{}").status(404),
+    });
+
+    let recorder = client(
+        Cassette::record(&path, ReqwestTransport::new()),
+        &server.url,
+    )
+    .await
+    .unwrap();
+    assert!(recorder.user("spez").await.is_ok());
+    assert!(recorder.user("spez").await.is_err());
+    assert!(recorder.user("spez").await.is_ok());
+
+    // The same route is answered in the recorded order, then the last answer is repeated.
+    let replayer = client(Cassette::replay(&path).unwrap(), "http://replay.invalid")
+        .await
+        .unwrap();
+    assert!(replayer.user("spez").await.is_ok());
+    assert!(matches!(
+        replayer.user("spez").await,
+        Err(Error::Status(404))
+    ));
+    assert!(replayer.user("spez").await.is_ok());
+    assert!(replayer.user("spez").await.is_ok());
+
+    // Nothing but the cassette is left behind.
+    let mut recording = path.file_name().unwrap().to_os_string();
+    recording.push(".recording");
+    assert!(!path.with_file_name(recording).exists());
+    std::fs::remove_file(&path).unwrap();
+}
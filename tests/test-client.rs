@@ -0,0 +1,424 @@
+mod common;
+
+use common::{
+    client, client_with_retries, endpoints, fixture, token_response, MockServer, Response,
+};
+use reqwest::header::{HeaderMap, HeaderValue};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tranquility::client::{builder::user_agent, ratelimit::RateLimiter, retry::RetryPolicy};
+use tranquility::prelude::*;
+
+/// Fails the first `failures` requests outside of the token endpoint with the given response.
+fn flaky(failures: usize, failure: Response) -> MockServer {
+    let calls = AtomicUsize::new(0);
+    MockServer::start(move |request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first"),
+        _ if calls.fetch_add(1, Ordering::SeqCst) < failures => failure.clone(),
+        _ => Response::json(&fixture("user-about")),
+    })
+}
+
+fn api_requests(server: &MockServer) -> usize {
+    server
+        .requests()
+        .iter()
+        .filter(|r| r.path != "/api/v1/access_token")
+        .count()
+}
+
+#[tokio::test]
+async fn test_local_endpoints() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first"),
+        "/user/spez/about" => Response::json(&fixture("user-about")),
+        _ => Response::json("{}").status(404),
+    });
+
+    let auth = BasicAuth::new("id", "secret", "user", "password").await;
+    let client = ClientBuilder::new("tranquility tests")
+        .endpoints(endpoints(&server))
+        .default_header("X-Test", "yes")
+        .build(auth)
+        .await
+        .unwrap();
+    let user = client.user("spez").await.unwrap();
+    assert_eq!(user.name, "spez");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].method, "POST");
+    assert!(requests[0].body.contains("grant_type=password"));
+    assert_eq!(requests[1].query, "raw_json=1");
+    assert_eq!(
+        requests[1].headers.get("authorization").map(String::as_str),
+        Some("Bearer first")
+    );
+    assert_eq!(
+        requests[1].headers.get("x-test").map(String::as_str),
+        Some("yes")
+    );
+    // The user agent is sent when logging in as well.
+    for request in &requests {
+        assert_eq!(
+            request.headers.get("user-agent").map(String::as_str),
+            Some("tranquility tests")
+        );
+    }
+}
+
+#[test]
+fn test_user_agent() {
+    assert_eq!(
+        user_agent("linux", "my-bot", "v1.0.0", "/u/someone"),
+        "linux:my-bot:v1.0.0 (by /u/someone)"
+    );
+}
+
+#[tokio::test]
+async fn test_logout() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first"),
+        "/api/v1/revoke_token" => Response::json("").status(204),
+        _ => Response::json("{}").status(404),
+    });
+
+    let client = client(&server).await;
+    client.logout().await.unwrap();
+    assert!(matches!(client.user("spez").await, Err(Error::LoggedOut)));
+
+    let revoked = server.requests().pop().unwrap();
+    assert_eq!(revoked.path, "/api/v1/revoke_token");
+    assert!(revoked.body.contains("token=first"));
+    assert_eq!(
+        revoked.headers.get("user-agent").map(String::as_str),
+        Some("tranquility tests")
+    );
+}
+
+#[tokio::test]
+async fn test_missing_rate_limit_headers() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first").without_headers(),
+        _ => Response::json(&fixture("user-about")).without_headers(),
+    });
+
+    let client = client(&server).await;
+    assert!(client.user("spez").await.is_ok());
+    assert!(client.rate_limit().is_none());
+}
+
+#[tokio::test]
+async fn test_rate_limit_reported() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first").without_headers(),
+        _ => Response::json(&fixture("user-about"))
+            .without_headers()
+            .header("x-ratelimit-used", "598")
+            .header("x-ratelimit-remaining", "2.0")
+            .header("x-ratelimit-reset", "300"),
+    });
+
+    let client = client(&server).await;
+    client.user("spez").await.unwrap();
+    let budget = client.rate_limit().expect("No rate limit reported");
+    assert_eq!(budget.used, 598);
+    assert_eq!(budget.remaining, 2.0);
+    assert!(budget.reset <= Duration::from_secs(300));
+    assert!(budget.reset > Duration::from_secs(299));
+}
+
+fn budget(remaining: &str, reset: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-ratelimit-used", HeaderValue::from_static("598"));
+    headers.insert("x-ratelimit-remaining", remaining.parse().unwrap());
+    headers.insert("x-ratelimit-reset", reset.parse().unwrap());
+    headers
+}
+
+fn assert_about(delay: Duration, expected: Duration) {
+    let margin = Duration::from_millis(50);
+    assert!(
+        delay + margin >= expected && delay <= expected + margin,
+        "expected a delay of about {:?}, got {:?}",
+        expected,
+        delay
+    );
+}
+
+#[test]
+fn test_rate_limit_spacing() {
+    let limiter = RateLimiter::new();
+    let now = Instant::now();
+    assert_eq!(limiter.reserve(now).unwrap(), Duration::from_secs(0));
+
+    // Two requests left in ten seconds, so they are spread five seconds apart.
+    limiter.update(&budget("2.0", "10")).unwrap();
+    let now = Instant::now();
+    assert_about(limiter.reserve(now).unwrap(), Duration::from_secs(0));
+    assert_about(limiter.reserve(now).unwrap(), Duration::from_secs(5));
+
+    // The budget is used up, the remaining requests wait for the reset and go one at a time.
+    // The pace last reported by reddit, five seconds, keeps them apart.
+    assert_about(limiter.reserve(now).unwrap(), Duration::from_secs(10));
+    assert_about(limiter.reserve(now).unwrap(), Duration::from_secs(15));
+    assert_about(limiter.reserve(now).unwrap(), Duration::from_secs(20));
+}
+
+#[test]
+fn test_rate_limit_bad_headers() {
+    // Values that are not usable numbers are ignored like missing headers.
+    let limiter = RateLimiter::new();
+    for (remaining, reset) in &[("NaN", "10"), ("2.0", "inf"), ("-1", "10"), ("2.0", "NaN")] {
+        limiter.update(&budget(remaining, reset)).unwrap();
+        assert!(limiter.current().is_none());
+    }
+
+    // Huge values are clamped instead of overflowing.
+    limiter.update(&budget("1e300", "1e300")).unwrap();
+    let current = limiter.current().expect("No rate limit reported");
+    assert!(current.reset <= Duration::from_secs(3600));
+    assert_about(
+        limiter.reserve(Instant::now()).unwrap(),
+        Duration::from_secs(0),
+    );
+    assert_about(
+        limiter.reserve(Instant::now()).unwrap(),
+        Duration::from_secs(0),
+    );
+}
+
+#[test]
+fn test_rate_limit_exhausted() {
+    // Without a known pace, the requests after the reset are sent a second apart.
+    let limiter = RateLimiter::new();
+    limiter.update(&budget("0", "2")).unwrap();
+    let now = Instant::now();
+    assert_about(limiter.reserve(now).unwrap(), Duration::from_secs(2));
+    assert_about(limiter.reserve(now).unwrap(), Duration::from_secs(3));
+
+    // A new budget is spread over what is left of the window after the requests already waiting.
+    limiter.update(&budget("10.0", "10")).unwrap();
+    assert_about(limiter.reserve(now).unwrap(), Duration::from_secs(4));
+    assert_about(limiter.reserve(now).unwrap(), Duration::from_millis(4600));
+}
+
+#[tokio::test]
+async fn test_retry_transient_errors() {
+    let server = flaky(2, Response::json("{}").status(503));
+    let retry = RetryPolicy::new().base_delay(Duration::from_millis(10));
+    let client = client_with_retries(&server, retry).await;
+    assert!(client.user("spez").await.is_ok());
+    assert_eq!(api_requests(&server), 3);
+}
+
+#[tokio::test]
+async fn test_retry_after() {
+    let server = flaky(
+        1,
+        Response::json("{}").status(429).header("Retry-After", "1"),
+    );
+    let client = client(&server).await;
+    let start = Instant::now();
+    assert!(client.user("spez").await.is_ok());
+    assert!(start.elapsed() >= Duration::from_millis(900));
+}
+
+#[tokio::test]
+async fn test_retry_after_over_max_delay() {
+    // Waiting a minute is longer than the policy allows, so the response is returned right away.
+    let server = flaky(
+        1,
+        Response::json("{}").status(429).header("Retry-After", "60"),
+    );
+    let client = client(&server).await;
+    let start = Instant::now();
+    assert!(matches!(client.user("spez").await, Err(Error::Status(429))));
+    assert!(start.elapsed() < Duration::from_secs(30));
+    assert_eq!(api_requests(&server), 1);
+}
+
+#[tokio::test]
+async fn test_post_retried_only_when_allowed() {
+    let server = flaky(1, Response::json("{}").status(502));
+    let retry = RetryPolicy::new().base_delay(Duration::from_millis(10));
+    let client = client_with_retries(&server, retry.clone()).await;
+    let result = client.compose("someone", "subject", "body").await;
+    assert!(matches!(result, Err(Error::Status(502))));
+    assert_eq!(api_requests(&server), 1);
+
+    let server = flaky(1, Response::json("{}").status(502));
+    let client = client_with_retries(&server, retry.retry_post(true)).await;
+    client.compose("someone", "subject", "body").await.unwrap();
+    assert_eq!(api_requests(&server), 2);
+}
+
+#[tokio::test]
+async fn test_api_errors() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first"),
+        "/api/compose" => Response::json(
+            r#"{"json": {"errors": [["RATELIMIT", "Looks like you've been doing that a lot. Take a break for 5 minutes before trying again.", "ratelimit"]]}}"#,
+        ),
+        "/api/read_all_messages" => Response::json(
+            r#"{"json": {"errors": [["USER_REQUIRED", "Please log in to do that.", null]]}}"#,
+        )
+        .status(400),
+        _ => Response::json(r#"{"message": "Forbidden", "error": 403}"#).status(403),
+    });
+
+    let client = client(&server).await;
+    let result = client.compose("someone", "subject", "body").await;
+    assert!(matches!(
+        result,
+        Err(Error::Api(ApiError::RateLimit(wait))) if wait == Duration::from_secs(300)
+    ));
+    assert!(matches!(
+        client.read_all_messages().await,
+        Err(Error::Api(ApiError::UserRequired))
+    ));
+    assert!(matches!(client.user("spez").await, Err(Error::Status(403))));
+}
+
+#[test]
+fn test_api_error_codes() {
+    assert_eq!(
+        ApiError::new("THREAD_LOCKED", "Comments are locked.", Some("parent")),
+        ApiError::ThreadLocked
+    );
+    assert_eq!(
+        ApiError::new(
+            "RATELIMIT",
+            "you are doing that too much. try again in 9 seconds.",
+            None
+        ),
+        ApiError::RateLimit(Duration::from_secs(9))
+    );
+    assert_eq!(
+        ApiError::new(
+            "RATELIMIT",
+            "try again in 18446744073709551615 hours.",
+            None
+        ),
+        ApiError::RateLimit(Duration::from_secs(u64::MAX))
+    );
+    assert_eq!(
+        ApiError::new("BAD_SR_NAME", "That name is invalid.", Some("sr")),
+        ApiError::Other(
+            "BAD_SR_NAME".into(),
+            "That name is invalid.".into(),
+            Some("sr".into())
+        )
+    );
+}
+
+/// Wraps the first child of a listing fixture the way reddit returns created things.
+fn created(name: &str) -> String {
+    let listing: serde_json::Value = serde_json::from_str(&fixture(name)).unwrap();
+    let thing = &listing["data"]["children"][0];
+    format!(
+        r#"{{"json": {{"errors": [], "data": {{"things": [{}]}}}}}}"#,
+        thing
+    )
+}
+
+#[tokio::test]
+async fn test_created_objects() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first"),
+        "/api/info" => Response::json(&fixture("info-link")),
+        "/api/comment" => Response::json(&created("info-comment")),
+        "/api/submit" => Response::json(
+            r#"{"json": {"errors": [], "data": {"url": "https://www.reddit.com/r/pcgaming/comments/ghravg/", "id": "ghravg", "name": "t3_ghravg"}}}"#,
+        ),
+        "/r/pcgaming/about" => Response::json(&fixture("subreddit-about")),
+        _ => Response::json("{}").status(404),
+    });
+
+    let client = client(&server).await;
+    let link = client.link("ghravg").await.unwrap();
+    assert_eq!(
+        link.permalink,
+        "/r/pcgaming/comments/ghravg/steam_might_be_working_on_a_loyalty_system_that/"
+    );
+
+    let comment = link.reply(&client, "A reply").await.unwrap();
+    assert_eq!(comment.name.as_ref(), "t1_fqanybx");
+    assert!(comment.permalink.ends_with("/fqanybx/"));
+
+    let subreddit = client.subreddit("pcgaming").await.unwrap();
+    let submitted = subreddit
+        .submit_text(&client, "Title", "Text")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(submitted.name, link.name);
+    assert_eq!(submitted.author, link.author);
+    assert_eq!(submitted.permalink, link.permalink);
+
+    let requests = server.requests();
+    let submit = requests.iter().find(|r| r.path == "/api/submit").unwrap();
+    assert!(submit.body.contains("kind=self"));
+    // The submitted Link is fetched once it has been created.
+    let lookup = requests.last().unwrap();
+    assert_eq!(lookup.path, "/api/info");
+    assert!(lookup.query.contains("id=t3_ghravg"));
+}
+
+#[tokio::test]
+async fn test_edit_and_delete() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first"),
+        "/api/info" if request.query.contains("id=t3_") => Response::json(&fixture("info-link")),
+        "/api/info" => Response::json(&fixture("info-comment")),
+        "/api/editusertext" if request.body.contains("thing_id=t3_") => {
+            Response::json(&created("info-link"))
+        }
+        "/api/editusertext" => Response::json(&created("info-comment")),
+        "/api/del" => Response::json("{}"),
+        _ => Response::json("{}").status(404),
+    });
+    let client = client(&server).await;
+    let last_request = || server.requests().pop().unwrap();
+
+    let link = client.link("ghravg").await.unwrap();
+    let edited = link.edit(&client, "New text").await.unwrap();
+    assert_eq!(edited.name, link.name);
+    let request = last_request();
+    assert_eq!(request.path, "/api/editusertext");
+    // The parameters of a POST are sent as a form rather than in the URL.
+    assert_eq!(request.query, "raw_json=1");
+    assert_eq!(
+        request.headers.get("content-type").map(String::as_str),
+        Some("application/x-www-form-urlencoded")
+    );
+    assert!(request.body.contains("thing_id=t3_ghravg"));
+    assert!(request.body.contains("text=New+text"));
+
+    let comment = client.comment("fqanybx").await.unwrap();
+    let edited = comment.edit(&client, "New body").await.unwrap();
+    assert_eq!(edited.name, comment.name);
+    assert!(last_request().body.contains("thing_id=t1_fqanybx"));
+
+    link.delete(&client).await.unwrap();
+    let request = last_request();
+    assert_eq!(request.path, "/api/del");
+    assert!(request.body.contains("id=t3_ghravg"));
+    comment.delete(&client).await.unwrap();
+    assert!(last_request().body.contains("id=t1_fqanybx"));
+}
+
+#[tokio::test]
+async fn test_missing_things() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first"),
+        "/api/info" => Response::json(r#"{"kind": "Listing", "data": {"children": []}}"#),
+        _ => Response::json("{}").status(404),
+    });
+    let client = client(&server).await;
+    assert!(matches!(client.link("gone").await, Err(Error::Custom(_))));
+    assert!(matches!(
+        client.comment("gone").await,
+        Err(Error::Custom(_))
+    ));
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use common::{client, fixture, token_response, MockServer, Response};
+use tranquility::model::commenttree::CommentTree;
+use tranquility::model::misc::{CommentOptions, CommentSort};
+
+fn article_server() -> MockServer {
+    MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first"),
+        "/api/info" => Response::json(&fixture("info-link")),
+        "/comments/ghravg/" => Response::json(&fixture("article-comments")),
+        _ => Response::json("{}").status(404),
+    })
+}
+
+#[tokio::test]
+async fn test_link_replies() {
+    let server = article_server();
+    let client = client(&server).await;
+    let link = client.link("ghravg").await.unwrap();
+
+    let replies = link.replies(&client, &CommentOptions::new()).await.unwrap();
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0].name.as_ref(), "t1_fr11tmm");
+
+    let request = server.requests().pop().unwrap();
+    assert_eq!(request.path, "/comments/ghravg/");
+    assert_eq!(request.query, "raw_json=1");
+}
+
+#[tokio::test]
+async fn test_comment_options() {
+    let server = article_server();
+    let client = client(&server).await;
+    let link = client.link("ghravg").await.unwrap();
+
+    let options = CommentOptions::new()
+        .sort(CommentSort::Best)
+        .depth(2)
+        .limit(50);
+    let tree = link.comment_tree(&client, &options).await.unwrap();
+    assert_eq!(tree.flatten().len(), 3);
+
+    let request = server.requests().pop().unwrap();
+    assert_eq!(request.query, "raw_json=1&sort=confidence&depth=2&limit=50");
+
+    let options = CommentOptions::new().sort(CommentSort::New);
+    link.replies(&client, &options).await.unwrap();
+    let request = server.requests().pop().unwrap();
+    assert_eq!(request.query, "raw_json=1&sort=new");
+}
+
+fn comment(id: &str, parent: &str, replies: &[String]) -> String {
+    format!(
+        r#"{{"kind": "t1", "data": {{"author": "someone", "link_id": "t3_ghravg", "subreddit_id": "t5_2qhfg", "parent_id": "{}", "name": "t1_{}", "body": "{}", "body_html": "", "replies": {}}}}}"#,
+        parent,
+        id,
+        id,
+        listing(replies)
+    )
+}
+
+fn more(name: &str, parent: &str, children: &[&str]) -> String {
+    format!(
+        r#"{{"kind": "more", "data": {{"count": {}, "name": "{}", "id": "{}", "parent_id": "{}", "depth": 1, "children": {:?}, "after": null, "before": null}}}}"#,
+        children.len(),
+        name,
+        name.trim_start_matches("t1_"),
+        parent,
+        children
+    )
+}
+
+fn listing(children: &[String]) -> String {
+    format!(
+        r#"{{"kind": "Listing", "data": {{"modhash": null, "dist": null, "children": [{}], "after": null, "before": null}}}}"#,
+        children.join(",")
+    )
+}
+
+#[tokio::test]
+async fn test_expand_more() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first"),
+        "/api/info" => Response::json(&fixture("info-link")),
+        "/comments/ghravg/" => Response::json(&format!(
+            "[{}, {}]",
+            listing(&[]),
+            listing(&[
+                comment("c1", "t3_ghravg", &[more("t1__", "t1_c1", &[])]),
+                more("t1_c2", "t3_ghravg", &["c2", "c3"]),
+            ])
+        )),
+        "/api/morechildren" => Response::json(&format!(
+            r#"{{"json": {{"errors": [], "data": {{"things": [{}, {}, {}]}}}}}}"#,
+            comment("c2", "t3_ghravg", &[]),
+            comment("c3", "t1_c2", &[]),
+            // Placeholders that were already expanded are not requested again.
+            more("t1_c2", "t3_ghravg", &["c2", "c3"]),
+        )),
+        // The continued thread ends in the same placeholder it was loaded from.
+        "/comments/ghravg/_/c1" => Response::json(&format!(
+            "[{}, {}]",
+            listing(&[]),
+            listing(&[comment(
+                "c1",
+                "t3_ghravg",
+                &[comment("c4", "t1_c1", &[more("t1__", "t1_c1", &[])])]
+            )])
+        )),
+        _ => Response::json("{}").status(404),
+    });
+    let client = client(&server).await;
+    let link = client.link("ghravg").await.unwrap();
+
+    let replies = link
+        .all_replies(&client, &CommentOptions::new().depth(1))
+        .await
+        .unwrap();
+    let names: Vec<&str> = replies.iter().map(|c| c.name.as_ref()).collect();
+    assert_eq!(names, vec!["t1_c1", "t1_c2"]);
+    let tree = CommentTree::from(replies);
+    assert_eq!(tree.flatten().len(), 4);
+
+    let requests = server.requests();
+    let more: Vec<_> = requests
+        .iter()
+        .filter(|r| r.path == "/api/morechildren")
+        .collect();
+    assert_eq!(more.len(), 1);
+    assert!(more[0].query.contains("children=c2%2Cc3"));
+    let continued: Vec<_> = requests
+        .iter()
+        .filter(|r| r.path == "/comments/ghravg/_/c1")
+        .collect();
+    assert_eq!(continued.len(), 1);
+    assert!(!continued[0].query.contains("depth"));
+}
+
+#[tokio::test]
+async fn test_more_children_batches() {
+    // The fixture holds a placeholder for 274 top-level comments besides two small ones.
+    let article: serde_json::Value = serde_json::from_str(&fixture("morechildren")).unwrap();
+    let info = article[0].to_string();
+    let server = MockServer::start(move |request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first"),
+        "/api/info" => Response::json(&info),
+        "/comments/gn62cf/" => Response::json(&fixture("morechildren")),
+        "/api/morechildren" => {
+            Response::json(r#"{"json": {"errors": [], "data": {"things": []}}}"#)
+        }
+        _ => Response::json("{}").status(404),
+    });
+    let client = client(&server).await;
+    let link = client.link("gn62cf").await.unwrap();
+    link.all_replies(&client, &CommentOptions::new())
+        .await
+        .unwrap();
+
+    let batches: Vec<Vec<String>> = server
+        .requests()
+        .into_iter()
+        .filter(|r| r.path == "/api/morechildren")
+        .map(|r| {
+            let children = r
+                .query
+                .split('&')
+                .find(|pair| pair.starts_with("children="))
+                .unwrap()
+                .trim_start_matches("children=")
+                .to_string();
+            children.split("%2C").map(String::from).collect()
+        })
+        .collect();
+    let mut sizes: Vec<usize> = batches.iter().map(Vec::len).collect();
+    sizes.sort_unstable();
+    assert_eq!(sizes, vec![1, 1, 74, 100, 100]);
+
+    // Every ID is asked for exactly once.
+    let mut ids: Vec<&String> = batches.iter().flatten().collect();
+    let total = ids.len();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), total);
+    assert_eq!(total, 276);
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::unnecessary_first_then_check)]
+
 use std::convert::{TryFrom, TryInto};
 use std::{fs::File, io::prelude::*, path::Path};
 use tranquility::model::prelude::*;
@@ -72,3 +74,58 @@ fn test_morechildren() {
     let more: More = Thing::try_into(things2.remove(things2.len() - 1)).unwrap();
     assert_eq!(more.children.len(), 274)
 }
+
+#[test]
+fn test_comment_tree() {
+    let things: Vec<Thing> = deser_from_file!("article-comments", Vec<Thing>);
+    let tree = CommentTree::try_from(things).unwrap();
+    assert_eq!(tree.len(), 3);
+    assert_eq!(tree.roots().count(), 1);
+    let depths: Vec<usize> = tree.depth_first().map(|node| node.depth).collect();
+    assert_eq!(depths, vec![0, 1, 2]);
+    let deepest = tree.breadth_first().last().unwrap();
+    let ancestors: Vec<&str> = tree
+        .ancestors(&deepest.comment.name)
+        .map(|node| node.comment.name.as_ref())
+        .collect();
+    assert_eq!(ancestors, vec!["t1_fr1xpdk", "t1_fr11tmm"]);
+    let parent = tree.parent(&deepest.comment.name).unwrap();
+    assert_eq!(parent.comment.name, deepest.comment.parent_id);
+    assert!(tree.more().is_empty());
+}
+
+#[test]
+fn test_comment_tree_more() {
+    let things: Vec<Thing> = deser_from_file!("morechildren", Vec<Thing>);
+    let tree = CommentTree::try_from(things).unwrap();
+    assert_eq!(tree.roots().count(), 3);
+    assert_eq!(tree.more().len(), 3);
+    let total: usize = tree.more().iter().map(|more| more.children.len()).sum();
+    assert_eq!(total, 276);
+    assert_eq!(tree.flatten().len(), 3);
+}
+
+#[test]
+fn test_message_inbox() {
+    let thing: Thing = deser_from_file!("message-inbox", Thing);
+    let listing: Listing = Thing::try_into(thing).unwrap();
+    let messages: Vec<Message> = Listing::try_into(listing).unwrap();
+    assert_eq!(messages.len(), 3);
+    assert!(!messages[0].was_comment);
+    assert!(messages[1].was_comment);
+    assert_eq!(messages[2].parent_id.as_ref().unwrap().prefix(), "t1");
+}
+
+#[test]
+fn test_inbox_items() {
+    let thing: Thing = deser_from_file!("message-inbox", Thing);
+    let listing: Listing = Thing::try_into(thing).unwrap();
+    let items: Vec<InboxItem> = listing
+        .children
+        .into_iter()
+        .map(|thing| InboxItem::try_from(thing).unwrap())
+        .collect();
+    assert!(matches!(items[0], InboxItem::Message(_)));
+    assert!(matches!(items[1], InboxItem::Mention(_)));
+    assert!(matches!(items[2], InboxItem::CommentReply(_)));
+}
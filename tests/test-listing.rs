@@ -0,0 +1,216 @@
+mod common;
+
+use common::{client, fixture, link, listing, token_response, MockServer, Request, Response};
+use futures::TryStreamExt;
+use tranquility::model::{
+    link::Link,
+    misc::{LinkSort, ListingOptions},
+};
+
+fn page(ids: &[&str], after: Option<&str>) -> Response {
+    let children: Vec<String> = ids.iter().map(|id| link(id)).collect();
+    listing(&children, after)
+}
+
+fn param<'a>(request: &'a Request, name: &str) -> Option<&'a str> {
+    request.query.split('&').find_map(|pair| {
+        let mut parts = pair.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) if key == name => Some(value),
+            _ => None,
+        }
+    })
+}
+
+/// Serves five links in pages of two, following the `after` cursor.
+fn listing_server() -> MockServer {
+    MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first"),
+        "/r/pcgaming/about" => Response::json(&fixture("subreddit-about")),
+        "/user/spez/about" => Response::json(&fixture("user-about")),
+        "/r/pcgaming/new"
+        | "/r/pcgaming/hot"
+        | "/r/pcgaming/top"
+        | "/r/pcgaming/controversial"
+        | "/user/spez/submitted" => match param(request, "after") {
+            None => page(&["a", "b"], Some("t3_b")),
+            Some("t3_b") => page(&["c", "d"], Some("t3_d")),
+            _ => page(&["e"], None),
+        },
+        _ => Response::json("{}").status(404),
+    })
+}
+
+fn listing_requests(server: &MockServer, path: &str) -> Vec<Request> {
+    server
+        .requests()
+        .into_iter()
+        .filter(|r| r.path == path)
+        .collect()
+}
+
+fn names(links: &[Link]) -> Vec<&str> {
+    links.iter().map(|l| l.name.as_ref()).collect()
+}
+
+#[tokio::test]
+async fn test_stream_follows_after() {
+    let server = listing_server();
+    let client = client(&server).await;
+    let subreddit = client.subreddit("pcgaming").await.unwrap();
+
+    let links: Vec<Link> = subreddit
+        .new_stream(&client, &ListingOptions::new())
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(names(&links), vec!["t3_a", "t3_b", "t3_c", "t3_d", "t3_e"]);
+
+    let requests = listing_requests(&server, "/r/pcgaming/new");
+    assert_eq!(requests.len(), 3);
+    assert_eq!(param(&requests[0], "after"), None);
+    assert_eq!(param(&requests[1], "after"), Some("t3_b"));
+    assert_eq!(param(&requests[2], "after"), Some("t3_d"));
+}
+
+#[tokio::test]
+async fn test_stream_count_progression() {
+    let server = listing_server();
+    let client = client(&server).await;
+    let subreddit = client.subreddit("pcgaming").await.unwrap();
+
+    let options = ListingOptions::new().count(10);
+    let links: Vec<Link> = subreddit
+        .new_stream(&client, &options)
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(links.len(), 5);
+
+    let requests = listing_requests(&server, "/r/pcgaming/new");
+    let counts: Vec<Option<&str>> = requests.iter().map(|r| param(r, "count")).collect();
+    assert_eq!(counts, vec![Some("10"), Some("12"), Some("14")]);
+}
+
+#[tokio::test]
+async fn test_stream_limit() {
+    let server = listing_server();
+    let client = client(&server).await;
+    let subreddit = client.subreddit("pcgaming").await.unwrap();
+
+    let links: Vec<Link> = subreddit
+        .new_stream(&client, &ListingOptions::new().limit(3))
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(names(&links), vec!["t3_a", "t3_b", "t3_c"]);
+
+    // The last page only asks for the items still missing, and no page is fetched after the limit.
+    let requests = listing_requests(&server, "/r/pcgaming/new");
+    assert_eq!(requests.len(), 2);
+    assert_eq!(param(&requests[0], "limit"), Some("3"));
+    assert_eq!(param(&requests[1], "limit"), Some("1"));
+}
+
+#[tokio::test]
+async fn test_listing_pages() {
+    let server = listing_server();
+    let client = client(&server).await;
+    let subreddit = client.subreddit("pcgaming").await.unwrap();
+
+    // Without a limit reddit's default of 25 items is asked for, even if pages come back shorter.
+    let links = subreddit
+        .hot(&client, &ListingOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(links.len(), 5);
+    let requests = listing_requests(&server, "/r/pcgaming/hot");
+    let limits: Vec<Option<&str>> = requests.iter().map(|r| param(r, "limit")).collect();
+    assert_eq!(limits, vec![Some("25"), Some("23"), Some("21")]);
+
+    let user = client.user("spez").await.unwrap();
+    let submitted: Vec<Link> = user
+        .submitted(&client, &ListingOptions::new().limit(4))
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(names(&submitted), vec!["t3_a", "t3_b", "t3_c", "t3_d"]);
+}
+
+#[tokio::test]
+async fn test_listing_options() {
+    let server = listing_server();
+    let client = client(&server).await;
+    let subreddit = client.subreddit("pcgaming").await.unwrap();
+
+    let options = ListingOptions::new().limit(2).show_all().sr_detail();
+    subreddit
+        .top(&client, LinkSort::Week, &options)
+        .await
+        .unwrap();
+    let request = listing_requests(&server, "/r/pcgaming/top").remove(0);
+    assert_eq!(param(&request, "t"), Some("week"));
+    assert_eq!(param(&request, "limit"), Some("2"));
+    assert_eq!(param(&request, "show"), Some("all"));
+    assert_eq!(param(&request, "sr_detail"), Some("true"));
+
+    let links: Vec<Link> = subreddit
+        .controversial_stream(&client, LinkSort::All, &ListingOptions::new().limit(3))
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(links.len(), 3);
+    // The time period is kept on every page.
+    let requests = listing_requests(&server, "/r/pcgaming/controversial");
+    assert_eq!(requests.len(), 2);
+    assert!(requests.iter().all(|r| param(r, "t") == Some("all")));
+    assert!(requests.iter().all(|r| param(r, "show").is_none()));
+}
+
+fn page_before(ids: &[&str], before: Option<&str>) -> Response {
+    let children: Vec<String> = ids.iter().map(|id| link(id)).collect();
+    let before = before.map_or("null".to_string(), |b| format!(r#""{}""#, b));
+    Response::json(&format!(
+        r#"{{"kind": "Listing", "data": {{"modhash": null, "dist": {}, "children": [{}], "after": null, "before": {}}}}}"#,
+        children.len(),
+        children.join(","),
+        before
+    ))
+}
+
+#[tokio::test]
+async fn test_stream_follows_before() {
+    // Listed newest first as a, b, c, d, e. Paging backwards from e walks towards a.
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first"),
+        "/r/pcgaming/about" => Response::json(&fixture("subreddit-about")),
+        "/r/pcgaming/new" => match param(request, "before") {
+            None => page(&["e"], None),
+            Some("t3_e") => page_before(&["c", "d"], Some("t3_c")),
+            Some("t3_c") => page_before(&["a", "b"], None),
+            _ => Response::json("{}").status(404),
+        },
+        _ => Response::json("{}").status(404),
+    });
+    let client = client(&server).await;
+    let subreddit = client.subreddit("pcgaming").await.unwrap();
+
+    let cursor = subreddit
+        .new(&client, &ListingOptions::new().limit(1))
+        .await
+        .unwrap()
+        .remove(0)
+        .name;
+    let links: Vec<Link> = subreddit
+        .new_stream(&client, &ListingOptions::new().before(cursor))
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(names(&links), vec!["t3_d", "t3_c", "t3_b", "t3_a"]);
+
+    let requests = listing_requests(&server, "/r/pcgaming/new");
+    assert_eq!(requests.len(), 3);
+    assert_eq!(param(&requests[1], "before"), Some("t3_e"));
+    assert_eq!(param(&requests[2], "before"), Some("t3_c"));
+    assert!(requests.iter().all(|r| param(r, "after").is_none()));
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use common::{client, fixture, token_response, MockServer, Response};
+use futures::{StreamExt, TryStreamExt};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tranquility::model::{
+    message::{InboxItem, Message, Reply},
+    misc::ListingOptions,
+    subreddit::SubmitBuilder,
+};
+use tranquility::prelude::*;
+use tranquility::stream::PollOptions;
+
+fn message_server() -> MockServer {
+    MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first"),
+        "/message/inbox" => Response::json(&fixture("message-inbox")),
+        "/api/compose" if request.body.contains("to=nobody") => Response::json(
+            r#"{"json": {"errors": [["USER_DOESNT_EXIST", "that user doesn't exist", "to"]]}}"#,
+        ),
+        "/api/compose" => Response::json(r#"{"json": {"errors": []}}"#),
+        "/api/read_message"
+        | "/api/unread_message"
+        | "/api/del_msg"
+        | "/api/block"
+        | "/api/read_all_messages" => Response::json("{}"),
+        _ => Response::json("{}").status(404),
+    })
+}
+
+fn last_request(server: &MockServer) -> common::Request {
+    server.requests().pop().unwrap()
+}
+
+#[tokio::test]
+async fn test_compose() {
+    let server = message_server();
+    let client = client(&server).await;
+
+    client
+        .compose("/r/rust", "A subject", "Some text")
+        .await
+        .unwrap();
+    let request = last_request(&server);
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/api/compose");
+    assert!(request.body.contains("to=%2Fr%2Frust"));
+    assert!(request.body.contains("subject=A+subject"));
+    assert!(request.body.contains("text=Some+text"));
+
+    assert!(matches!(
+        client.compose("nobody", "A subject", "Some text").await,
+        Err(Error::Api(ApiError::Other(code, _, _))) if code == "USER_DOESNT_EXIST"
+    ));
+}
+
+#[tokio::test]
+async fn test_message_actions() {
+    let server = message_server();
+    let client = client(&server).await;
+    let messages: Vec<Message> = client
+        .inbox(&ListingOptions::new())
+        .try_collect()
+        .await
+        .unwrap();
+    let message = &messages[0];
+    assert_eq!(message.name.as_ref(), "t4_rxbmq2");
+
+    message.mark_read(&client).await.unwrap();
+    let request = last_request(&server);
+    assert_eq!(request.path, "/api/read_message");
+    assert!(request.body.contains("id=t4_rxbmq2"));
+
+    message.mark_unread(&client).await.unwrap();
+    assert_eq!(last_request(&server).path, "/api/unread_message");
+
+    message.delete(&client).await.unwrap();
+    assert_eq!(last_request(&server).path, "/api/del_msg");
+
+    message.block(&client).await.unwrap();
+    let request = last_request(&server);
+    assert_eq!(request.path, "/api/block");
+    assert!(request.body.contains("id=t4_rxbmq2"));
+
+    client.read_all_messages().await.unwrap();
+    assert_eq!(last_request(&server).path, "/api/read_all_messages");
+}
+
+#[tokio::test]
+async fn test_inbox_stream_marks_read() {
+    let reads = AtomicUsize::new(0);
+    let server = MockServer::start(move |request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first"),
+        "/message/unread" => Response::json(&fixture("message-inbox")),
+        // The first attempt to mark an item as read fails.
+        "/api/read_message" if reads.fetch_add(1, Ordering::SeqCst) == 0 => {
+            Response::json("{}").status(500)
+        }
+        "/api/read_message" => Response::json("{}"),
+        _ => Response::json("{}").status(404),
+    });
+    let client = client(&server).await;
+    let marked = || -> Vec<String> {
+        server
+            .requests()
+            .into_iter()
+            .filter(|r| r.path == "/api/read_message")
+            .map(|r| r.body)
+            .collect()
+    };
+    let name = |item: InboxItem| item.message().name.as_ref().to_string();
+
+    // Nothing is marked until the caller asks for the next item, so an unhandled item stays unread.
+    let mut stream = client.inbox_stream(true, PollOptions::new());
+    let first = stream.next().await.unwrap().unwrap();
+    assert_eq!(name(first), "t1_ghw9pkc");
+    assert!(marked().is_empty());
+
+    // Marking the first item fails, it is kept and marked again before the second item is returned.
+    assert!(matches!(stream.next().await, Some(Err(Error::Status(500)))));
+    let second = stream.next().await.unwrap().unwrap();
+    assert_eq!(name(second), "t1_ghwa1b2");
+    let third = stream.next().await.unwrap().unwrap();
+    assert_eq!(name(third), "t4_rxbmq2");
+    assert_eq!(marked().len(), 3);
+
+    // The last item is marked explicitly once it was handled, and only once.
+    stream.mark_read(&client).await.unwrap();
+    stream.mark_read(&client).await.unwrap();
+    let marked = marked();
+    assert_eq!(marked.len(), 4);
+    assert!(marked[0].contains("id=t1_ghw9pkc"));
+    assert!(marked[1].contains("id=t1_ghw9pkc"));
+    assert!(marked[2].contains("id=t1_ghwa1b2"));
+    assert!(marked[3].contains("id=t4_rxbmq2"));
+}
+
+#[tokio::test]
+async fn test_reply_scopes() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => Response::json(
+            r#"{"access_token": "first", "token_type": "bearer", "expires_in": 3600, "scope": "privatemessages"}"#,
+        ),
+        "/message/inbox" => Response::json(&fixture("message-inbox")),
+        "/api/comment" => Response::json(&format!(
+            r#"{{"json": {{"errors": [], "data": {{"things": [{}]}}}}}}"#,
+            r#"{"kind": "t4", "data": {"name": "t4_reply", "author": "user", "dest": "someone", "subject": "re: hi", "body": "", "body_html": "", "created_utc": 0.0, "context": "", "new": false, "was_comment": false}}"#
+        )),
+        _ => Response::json("{}").status(404),
+    });
+    let client = client(&server).await;
+    let messages: Vec<Message> = client
+        .inbox(&ListingOptions::new())
+        .try_collect()
+        .await
+        .unwrap();
+
+    // Replying to messages only needs the privatemessages scope, submitting links needs submit.
+    let reply = messages[0].reply(&client, "Some text").await.unwrap();
+    assert!(matches!(&reply, Reply::Message(message) if message.subject == "re: hi"));
+    assert_eq!(reply.name().as_ref(), "t4_reply");
+    assert!(matches!(
+        SubmitBuilder::new_text(&client, "rust", "A title", "Some text")
+            .send()
+            .await,
+        Err(Error::MissingScope(Scope::Submit))
+    ));
+}
@@ -0,0 +1,200 @@
+mod common;
+
+use common::{endpoints, fixture, MockServer, Response};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tranquility::auth::{scope::Scope, Auth};
+use tranquility::client::{endpoints::Endpoints, transport::ReqwestTransport};
+use tranquility::prelude::*;
+
+/// A token response for a permanent authorization, expiring within a minute so it is refreshed right away.
+fn expiring_token(access_token: &str) -> Response {
+    Response::json(&format!(
+        r#"{{"access_token": "{}", "refresh_token": "refresh", "token_type": "bearer", "expires_in": 30, "scope": "read identity"}}"#,
+        access_token
+    ))
+}
+
+fn token_requests(server: &MockServer) -> Vec<String> {
+    server
+        .requests()
+        .into_iter()
+        .filter(|r| r.path == "/api/v1/access_token")
+        .map(|r| r.body)
+        .collect()
+}
+
+#[test]
+fn test_authorize_url() {
+    let url = WebAuth::authorize_url(
+        &Endpoints::new(),
+        "id",
+        "http://localhost/callback",
+        "some-state",
+        &[Scope::Identity, Scope::Read],
+        true,
+    )
+    .unwrap();
+    assert_eq!(url.host_str(), Some("www.reddit.com"));
+    assert_eq!(url.path(), "/api/v1/authorize");
+    let query = url.query().unwrap();
+    assert!(query.contains("client_id=id"));
+    assert!(query.contains("response_type=code"));
+    assert!(query.contains("state=some-state"));
+    assert!(query.contains("redirect_uri=http%3A%2F%2Flocalhost%2Fcallback"));
+    assert!(query.contains("duration=permanent"));
+    assert!(query.contains("scope=identity+read"));
+
+    let local = Endpoints::new().www("http://localhost:8080");
+    let url =
+        WebAuth::authorize_url(&local, "id", "http://localhost/callback", "", &[], false).unwrap();
+    assert_eq!(url.host_str(), Some("localhost"));
+    assert_eq!(url.port(), Some(8080));
+    assert_eq!(url.path(), "/api/v1/authorize");
+    assert!(url.query().unwrap().contains("duration=temporary"));
+}
+
+#[tokio::test]
+async fn test_code_exchange() {
+    let attempts = AtomicUsize::new(0);
+    let server = MockServer::start(move |request| match request.path.as_str() {
+        "/api/v1/access_token" if attempts.fetch_add(1, Ordering::SeqCst) == 0 => {
+            Response::json("{}").status(500)
+        }
+        "/api/v1/access_token" => expiring_token("first"),
+        _ => Response::json("{}").status(404),
+    });
+    let auth = WebAuth::new("id", "secret", "http://localhost/callback", "the-code").await;
+    let transport = ReqwestTransport::new();
+
+    // The code survives a failed exchange, so logging in can be tried again.
+    assert!(matches!(
+        auth.login(&transport, &endpoints(&server), None).await,
+        Err(Error::Status(500))
+    ));
+    let token = auth
+        .login(&transport, &endpoints(&server), None)
+        .await
+        .unwrap();
+    assert_eq!(token.refresh_token.as_deref(), Some("refresh"));
+    assert_eq!(token.scopes, vec![Scope::Read, Scope::Identity]);
+
+    // Once exchanged, the refresh token is used instead.
+    auth.login(&transport, &endpoints(&server), Some(&token))
+        .await
+        .unwrap();
+
+    let bodies = token_requests(&server);
+    assert_eq!(bodies.len(), 3);
+    for body in &bodies[..2] {
+        assert!(body.contains("grant_type=authorization_code"));
+        assert!(body.contains("code=the-code"));
+        assert!(body.contains("redirect_uri=http%3A%2F%2Flocalhost%2Fcallback"));
+    }
+    assert!(bodies[2].contains("grant_type=refresh_token"));
+    assert!(bodies[2].contains("refresh_token=refresh"));
+}
+
+#[tokio::test]
+async fn test_web_refresh() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => expiring_token("first"),
+        "/user/spez/about" => Response::json(&fixture("user-about")),
+        _ => Response::json("{}").status(404),
+    });
+    let auth = WebAuth::new("id", "secret", "http://localhost/callback", "the-code").await;
+    let client = ClientBuilder::new("tranquility tests")
+        .endpoints(endpoints(&server))
+        .build(auth)
+        .await
+        .unwrap();
+
+    client.user("spez").await.unwrap();
+    let bodies = token_requests(&server);
+    assert_eq!(bodies.len(), 2);
+    assert!(bodies[0].contains("grant_type=authorization_code"));
+    assert!(bodies[1].contains("grant_type=refresh_token"));
+}
+
+fn application_server() -> MockServer {
+    MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => common::token_response("first"),
+        "/user/spez/about" => Response::json(&fixture("user-about")),
+        _ => Response::json("{}").status(404),
+    })
+}
+
+async fn application_client(server: &MockServer, auth: ApplicationAuth) -> Client<ApplicationAuth> {
+    ClientBuilder::new("tranquility tests")
+        .endpoints(endpoints(server))
+        .build(auth)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_application_grants() {
+    let server = application_server();
+    application_client(&server, ApplicationAuth::new("id", "secret").await).await;
+    application_client(
+        &server,
+        ApplicationAuth::installed("id", Some("device-1")).await,
+    )
+    .await;
+    application_client(&server, ApplicationAuth::installed("id", None).await).await;
+
+    let bodies = token_requests(&server);
+    assert_eq!(bodies[0], "grant_type=client_credentials");
+    assert_eq!(
+        bodies[1],
+        "grant_type=https%3A%2F%2Foauth.reddit.com%2Fgrants%2Finstalled_client&device_id=device-1"
+    );
+    assert!(bodies[2].ends_with("device_id=DO_NOT_TRACK_THIS_DEVICE"));
+}
+
+#[tokio::test]
+async fn test_application_read_only() {
+    let server = application_server();
+    let client = application_client(&server, ApplicationAuth::new("id", "secret").await).await;
+
+    assert!(client.user("spez").await.is_ok());
+    assert!(matches!(
+        client.compose("someone", "subject", "body").await,
+        Err(Error::ReadOnly)
+    ));
+    // The refused action never reaches reddit.
+    assert!(server.requests().iter().all(|r| r.path != "/api/compose"));
+}
+
+#[tokio::test]
+async fn test_logout_revokes_both_tokens() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/v1/access_token" => Response::json(
+            r#"{"access_token": "first", "refresh_token": "refresh", "token_type": "bearer", "expires_in": 3600, "scope": "*"}"#,
+        ),
+        // Revoking the refresh token fails, the access token must still be revoked.
+        "/api/v1/revoke_token" if request.body.contains("token_type_hint=refresh_token") => {
+            Response::json("{}").status(500)
+        }
+        "/api/v1/revoke_token" => Response::json("").status(204),
+        _ => Response::json("{}").status(404),
+    });
+    let auth = WebAuth::new("id", "secret", "http://localhost/callback", "the-code").await;
+    let client = ClientBuilder::new("tranquility tests")
+        .endpoints(endpoints(&server))
+        .build(auth)
+        .await
+        .unwrap();
+
+    assert!(matches!(client.logout().await, Err(Error::Status(500))));
+    assert!(matches!(client.user("spez").await, Err(Error::LoggedOut)));
+
+    let revoked: Vec<String> = server
+        .requests()
+        .into_iter()
+        .filter(|r| r.path == "/api/v1/revoke_token")
+        .map(|r| r.body)
+        .collect();
+    assert_eq!(revoked.len(), 2);
+    assert!(revoked[0].contains("token=refresh&token_type_hint=refresh_token"));
+    assert!(revoked[1].contains("token=first&token_type_hint=access_token"));
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use common::{client_with_retries, link, listing, token_response, MockServer, Response};
+use futures::StreamExt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tranquility::client::retry::RetryPolicy;
+use tranquility::model::link::Link;
+use tranquility::prelude::*;
+use tranquility::stream::PollOptions;
+
+async fn client(server: &MockServer) -> Client<BasicAuth> {
+    client_with_retries(server, RetryPolicy::never()).await
+}
+
+fn options() -> PollOptions {
+    PollOptions::new()
+        .min_delay(Duration::from_millis(1))
+        .max_delay(Duration::from_millis(5))
+}
+
+fn links(ids: &[&str]) -> Response {
+    let children: Vec<String> = ids.iter().map(|id| link(id)).collect();
+    listing(&children, None)
+}
+
+/// Answers each poll of the path with the next response, repeating the last one.
+fn polled(path: &'static str, responses: Vec<Response>) -> MockServer {
+    let polls = AtomicUsize::new(0);
+    MockServer::start(move |request| match request.path.as_str() {
+        "/api/v1/access_token" => token_response("first"),
+        p if p == path => {
+            let poll = polls.fetch_add(1, Ordering::SeqCst);
+            responses[poll.min(responses.len() - 1)].clone()
+        }
+        _ => Response::json("{}").status(404),
+    })
+}
+
+fn polls(server: &MockServer, path: &str) -> usize {
+    server.requests().iter().filter(|r| r.path == path).count()
+}
+
+async fn names(stream: impl futures::Stream<Item = Result<Link, Error>>, n: usize) -> Vec<String> {
+    stream
+        .take(n)
+        .map(|link| link.unwrap().name.as_ref().to_string())
+        .collect()
+        .await
+}
+
+#[tokio::test]
+async fn test_submission_stream_deduplicates() {
+    // Listings are newest first, items seen in an earlier poll must not come back.
+    let server = polled(
+        "/r/pcgaming+rust/new",
+        vec![
+            links(&["b", "a"]),
+            links(&["c", "b", "a"]),
+            links(&["d", "c"]),
+        ],
+    );
+    let client = client(&server).await;
+
+    let stream = client.submission_stream(&["pcgaming", "rust"], options());
+    let names = names(stream, 4).await;
+    assert_eq!(names, vec!["t3_a", "t3_b", "t3_c", "t3_d"]);
+    assert_eq!(polls(&server, "/r/pcgaming+rust/new"), 3);
+}
+
+#[tokio::test]
+async fn test_submission_stream_skip_existing() {
+    let server = polled(
+        "/r/pcgaming/new",
+        vec![
+            links(&["b", "a"]),
+            links(&["b", "a"]),
+            links(&["c", "b", "a"]),
+        ],
+    );
+    let client = client(&server).await;
+
+    let stream = client.submission_stream(&["pcgaming"], options().skip_existing());
+    assert_eq!(names(stream, 1).await, vec!["t3_c"]);
+    assert_eq!(polls(&server, "/r/pcgaming/new"), 3);
+}
+
+#[tokio::test]
+async fn test_submission_stream_backoff() {
+    // Server errors and rate limiting are waited out instead of ending the stream.
+    let server = polled(
+        "/r/pcgaming/new",
+        vec![
+            links(&["a"]),
+            Response::json("{}").status(503),
+            Response::json("{}").status(429),
+            links(&["b", "a"]),
+            Response::json("{}").status(403),
+        ],
+    );
+    let client = client(&server).await;
+
+    let mut stream = client.submission_stream(&["pcgaming"], options());
+    let first = stream.next().await.unwrap().unwrap();
+    assert_eq!(first.name.as_ref(), "t3_a");
+    let second = stream.next().await.unwrap().unwrap();
+    assert_eq!(second.name.as_ref(), "t3_b");
+    assert_eq!(polls(&server, "/r/pcgaming/new"), 4);
+
+    // Other errors are returned to the caller.
+    assert!(matches!(stream.next().await, Some(Err(Error::Status(403)))));
+}
+
+fn comments(ids: &[&str]) -> Response {
+    let children: Vec<String> = ids
+        .iter()
+        .map(|id| {
+            format!(
+                r#"{{"kind": "t1", "data": {{"author": "someone", "link_id": "t3_a", "subreddit_id": "t5_2qh1i", "parent_id": "t3_a", "name": "t1_{}", "body": "", "body_html": "", "replies": null}}}}"#,
+                id
+            )
+        })
+        .collect();
+    listing(&children, None)
+}
+
+#[tokio::test]
+async fn test_comment_stream() {
+    let server = polled(
+        "/r/rust/comments",
+        vec![
+            comments(&["b", "a"]),
+            Response::json("{}").status(502),
+            comments(&["c", "b"]),
+        ],
+    );
+    let client = client(&server).await;
+
+    let stream = client.comment_stream(&["rust"], options());
+    let names: Vec<String> = stream
+        .take(3)
+        .map(|comment| comment.unwrap().name.as_ref().to_string())
+        .collect()
+        .await;
+    assert_eq!(names, vec!["t1_a", "t1_b", "t1_c"]);
+
+    let request = server
+        .requests()
+        .into_iter()
+        .find(|r| r.path == "/r/rust/comments")
+        .unwrap();
+    assert!(request.query.contains("limit=100"));
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::{client, fixture, token_response, MockServer, Response};
+use futures::future::join_all;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn logins(server: &MockServer) -> usize {
+    server
+        .requests()
+        .iter()
+        .filter(|r| r.path == "/api/v1/access_token")
+        .count()
+}
+
+#[tokio::test]
+async fn test_refresh_on_unauthorized() {
+    let logins_made = AtomicUsize::new(0);
+    let server = MockServer::start(move |request| match request.path.as_str() {
+        "/api/v1/access_token" => match logins_made.fetch_add(1, Ordering::SeqCst) {
+            0 => token_response("first"),
+            _ => token_response("second"),
+        },
+        "/user/spez/about" => match request.headers.get("authorization").map(String::as_str) {
+            Some("Bearer second") => Response::json(&fixture("user-about")),
+            _ => Response::json("{}").status(401),
+        },
+        _ => Response::json("{}").status(404),
+    });
+
+    let client = client(&server).await;
+    assert!(client.user("spez").await.is_ok());
+    assert!(client.user("spez").await.is_ok());
+    assert_eq!(logins(&server), 2);
+}
+
+#[tokio::test]
+async fn test_single_refresh_under_concurrency() {
+    let logins_made = AtomicUsize::new(0);
+    let server = MockServer::start(move |request| match request.path.as_str() {
+        // The first token expires within the refresh margin, so every request finds it expired.
+        "/api/v1/access_token" => match logins_made.fetch_add(1, Ordering::SeqCst) {
+            0 => Response::json(
+                r#"{"access_token": "first", "token_type": "bearer", "expires_in": 30, "scope": "*"}"#,
+            ),
+            _ => token_response("second"),
+        },
+        "/user/spez/about" => match request.headers.get("authorization").map(String::as_str) {
+            Some("Bearer second") => Response::json(&fixture("user-about")),
+            _ => Response::json("{}").status(401),
+        },
+        _ => Response::json("{}").status(404),
+    });
+
+    let client = client(&server).await;
+    let results = join_all((0..10).map(|_| client.user("spez"))).await;
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(logins(&server), 2);
+}
@@ -0,0 +1,142 @@
+use async_trait::async_trait;
+use futures::executor::block_on;
+use http::{HeaderMap, Method, StatusCode};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tranquility::client::{
+    endpoints::Endpoints,
+    retry::RetryPolicy,
+    transport::{HttpRequest, HttpResponse, HttpTransport, ReqwestTransport},
+};
+use tranquility::prelude::*;
+
+/// An in-memory transport answering from the fixtures, failing the first `failures` API requests.
+#[derive(Debug, Clone, Default)]
+struct FakeTransport {
+    requests: Arc<Mutex<Vec<HttpRequest>>>,
+    failures: Arc<Mutex<usize>>,
+}
+
+#[async_trait]
+impl HttpTransport for FakeTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, Error> {
+        self.requests.lock().unwrap().push(request.clone());
+        let path = request
+            .url
+            .trim_start_matches("https://fake")
+            .split('?')
+            .next()
+            .unwrap()
+            .to_string();
+
+        let body = match path.as_str() {
+            "/api/v1/access_token" => {
+                r#"{"access_token": "fake", "expires_in": 3600, "scope": "*"}"#.to_string()
+            }
+            _ => {
+                let mut failures = self.failures.lock().unwrap();
+                if *failures > 0 {
+                    *failures -= 1;
+                    return Err(Error::Transport("connection reset".into()));
+                }
+                match path.as_str() {
+                    "/user/spez/about" => std::fs::read_to_string("./tests/json/user-about.json")
+                        .expect("Failed to read file."),
+                    _ => "{}".to_string(),
+                }
+            }
+        };
+
+        Ok(HttpResponse {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body,
+        })
+    }
+}
+
+fn client(transport: FakeTransport) -> Client<BasicAuth> {
+    client_with_retries(
+        transport,
+        RetryPolicy::new().base_delay(Duration::from_millis(1)),
+    )
+}
+
+fn client_with_retries(transport: FakeTransport, retry: RetryPolicy) -> Client<BasicAuth> {
+    block_on(async {
+        let auth = BasicAuth::new("id", "secret", "user", "password").await;
+        ClientBuilder::new("tranquility tests")
+            .endpoints(Endpoints::new().oauth("https://fake").www("https://fake"))
+            .retry_policy(retry)
+            .transport(transport)
+            .build(auth)
+            .await
+            .expect("Failed to log in")
+    })
+}
+
+#[test]
+fn test_fake_transport() {
+    let transport = FakeTransport::default();
+    let client = client(transport.clone());
+    let user = block_on(client.user("spez")).unwrap();
+    assert_eq!(user.name, "spez");
+
+    let requests = transport.requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].body.contains("grant_type=password"));
+    assert_eq!(
+        requests[0].headers.get("authorization").unwrap(),
+        "Basic aWQ6c2VjcmV0"
+    );
+    assert_eq!(requests[1].url, "https://fake/user/spez/about?raw_json=1");
+    assert_eq!(
+        requests[1].headers.get("authorization").unwrap(),
+        "Bearer fake"
+    );
+}
+
+#[test]
+fn test_transport_errors_retried() {
+    let transport = FakeTransport::default();
+    *transport.failures.lock().unwrap() = 2;
+    let client = client(transport.clone());
+    assert!(block_on(client.user("spez")).is_ok());
+    assert_eq!(transport.requests.lock().unwrap().len(), 4);
+
+    *transport.failures.lock().unwrap() = 3;
+    assert!(matches!(
+        block_on(client.user("spez")),
+        Err(Error::Transport(_))
+    ));
+}
+
+#[test]
+fn test_retry_errors_configurable() {
+    let transport = FakeTransport::default();
+    let retry = RetryPolicy::new()
+        .base_delay(Duration::from_millis(1))
+        .retry_errors(|error| !matches!(error, Error::Transport(_)));
+    let client = client_with_retries(transport.clone(), retry);
+
+    *transport.failures.lock().unwrap() = 1;
+    assert!(matches!(
+        block_on(client.user("spez")),
+        Err(Error::Transport(_))
+    ));
+    // The token request and the single attempt.
+    assert_eq!(transport.requests.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_reqwest_errors_are_transport_errors() {
+    // Nothing listens on the port once the listener is dropped, so connecting fails.
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let request = HttpRequest::new(Method::GET, &format!("http://127.0.0.1:{}/", port));
+    let result = ReqwestTransport::new().send(request).await;
+    assert!(matches!(result, Err(Error::Transport(_))));
+}